@@ -1,6 +1,12 @@
+use std::path::{Path, PathBuf};
+
 use egui_macroquad::egui::Color32;
 
-use crate::palette::{Palette, Swatch};
+use crate::color::ColorSpace;
+use crate::curves::{CurveKind, CurveType};
+use crate::palette::{deduplicate_colors, ColorLink, LinkError, Palette, StepResize, Swatch};
+use crate::project::{Project, ProjectError};
+use crate::rendering::{palette_bounds, swatch_bounds, LayoutMode, PaletteLayout};
 use crate::viewport::Viewport;
 
 // =============================================================================
@@ -12,10 +18,21 @@ pub struct App {
     pub palette: Palette,
     /// Index of the currently selected swatch
     pub current_swatch_index: usize,
+    /// Index of the selected color (step) within the current swatch
+    pub selected_color_index: usize,
     /// Viewport for panning and zooming
     pub viewport: Viewport,
+    /// How swatches are arranged in the viewport
+    pub layout: PaletteLayout,
     /// Cached generated colors for each swatch (regenerated when swatches change)
     pub generated_colors: Vec<Vec<Color32>>,
+    /// File the palette was last saved to or opened from
+    pub file_path: Option<PathBuf>,
+    /// Whether the palette changed since it was last saved
+    pub dirty: bool,
+    /// Bumped on every change to the palette, so windows can cache what they
+    /// derive from it
+    pub revision: u64,
 }
 
 impl App {
@@ -25,8 +42,13 @@ impl App {
         let mut app = Self {
             palette,
             current_swatch_index: 0,
+            selected_color_index: 0,
             viewport: Viewport::default(),
+            layout: PaletteLayout::default(),
             generated_colors: Vec::new(),
+            file_path: None,
+            dirty: false,
+            revision: 0,
         };
         
         app.regenerate_all_colors();
@@ -45,29 +67,207 @@ impl App {
 
     /// Regenerate colors for all swatches
     pub fn regenerate_all_colors(&mut self) {
-        self.generated_colors = self
-            .palette
-            .swatches
-            .iter()
-            .map(|swatch| swatch.generate_colors())
+        self.revision += 1;
+        self.palette.resolve_links();
+        self.generated_colors = (0..self.palette.swatches.len())
+            .map(|index| self.palette.display_colors(index))
             .collect();
+        self.clamp_color_selection();
     }
 
-    /// Regenerate colors for the current swatch only
+    /// Regenerate colors for the current swatch, plus any swatch whose linked
+    /// control points follow a color that changed. Called after editing the
+    /// current swatch, so it also marks the palette as modified.
     pub fn regenerate_current_colors(&mut self) {
-        if self.current_swatch_index < self.generated_colors.len() {
-            self.generated_colors[self.current_swatch_index] =
-                self.palette.swatches[self.current_swatch_index].generate_colors();
+        self.mark_dirty();
+        let mut dirty = self.palette.resolve_links();
+        dirty.push(self.current_swatch_index);
+        for index in dirty {
+            if index < self.generated_colors.len() {
+                self.generated_colors[index] = self.palette.display_colors(index);
+            }
+        }
+        self.clamp_color_selection();
+    }
+
+    /// Keep the selected color inside the current swatch
+    fn clamp_color_selection(&mut self) {
+        let size = self.current_swatch().size;
+        self.selected_color_index = self.selected_color_index.min(size.saturating_sub(1));
+    }
+
+    /// Select a color (step) of the current swatch
+    pub fn select_color(&mut self, index: usize) {
+        self.selected_color_index = index;
+        self.clamp_color_selection();
+    }
+
+    /// Set the color at `step` of the current swatch by adding a control point
+    /// there, or recoloring the control point already at that step
+    pub fn pin_color(&mut self, step: usize, color: Color32) {
+        self.current_swatch_mut().pin_step(step, color);
+        self.regenerate_current_colors();
+    }
+
+    /// Remove the control point at `step` of the current swatch, if there is one
+    pub fn unpin_color(&mut self, step: usize) {
+        if self.current_swatch_mut().unpin_step(step) {
+            self.regenerate_current_colors();
+        }
+    }
+
+    /// Add, remove or resample steps of the current swatch, keeping pinned
+    /// colors on their steps. The selection follows the selected step.
+    pub fn resize_current_swatch(&mut self, resize: StepResize) {
+        let size = self.current_swatch().size;
+        if resize.new_size(size).is_none() {
+            return;
+        }
+        let selected = resize
+            .map_step(self.selected_color_index, size)
+            .unwrap_or(self.selected_color_index);
+        self.current_swatch_mut().resize(resize);
+        self.selected_color_index = selected;
+        self.regenerate_current_colors();
+    }
+
+    /// Lock `step` of the current swatch at `edited`, or at its generated
+    /// color if it wasn't edited; unlock it if it's locked already
+    pub fn toggle_step_lock(&mut self, step: usize, edited: Option<Color32>) {
+        let swatch = self.current_swatch_mut();
+        if swatch.is_step_locked(step) {
+            swatch.unlock_step(step);
+        } else if let Some(color) = edited {
+            swatch.lock_step_at(step, color);
+        } else {
+            swatch.lock_step(step);
         }
+        self.regenerate_current_colors();
+    }
+
+    /// Switch the current swatch to the next color space
+    pub fn cycle_color_space(&mut self) {
+        let all = ColorSpace::ALL;
+        let current = all.iter().position(|&s| s == self.current_swatch().color_space).unwrap_or(0);
+        self.current_swatch_mut().color_space = all[(current + 1) % all.len()];
+        self.regenerate_current_colors();
+    }
+
+    /// Switch the current swatch to the next curve type, with default parameters
+    pub fn cycle_curve(&mut self) {
+        // Bezier has no editor yet, so it is left out of the cycle
+        const CYCLE: [CurveKind; 4] = [CurveKind::Linear, CurveKind::EaseIn, CurveKind::EaseOut, CurveKind::EaseInOut];
+        let kind = self.current_swatch().interpolation_curve.kind();
+        let current = CYCLE.iter().position(|&k| k == kind).unwrap_or(0);
+        self.current_swatch_mut().interpolation_curve = CurveType::from_kind(CYCLE[(current + 1) % CYCLE.len()]);
+        self.regenerate_current_colors();
+    }
+
+    /// All generated colors of every swatch, in palette order
+    pub fn flattened_colors(&self) -> Vec<Color32> {
+        self.generated_colors.iter().flatten().copied().collect()
+    }
+
+    /// Generated colors with duplicates (within the palette's merge threshold)
+    /// removed, in palette order
+    pub fn unique_colors(&self) -> Vec<Color32> {
+        deduplicate_colors(&self.flattened_colors(), self.palette.merge_threshold)
+    }
+
+    /// Whether the palette uses more distinct colors than its budget allows
+    pub fn is_over_budget(&self) -> bool {
+        self.palette
+            .color_budget
+            .is_some_and(|budget| self.unique_colors().len() > budget)
+    }
+
+    /// Record an edit made directly to the palette
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+        self.revision += 1;
+    }
+
+    /// File name shown in the title, with `*` when there are unsaved changes
+    pub fn title(&self) -> String {
+        let name = self
+            .file_path
+            .as_deref()
+            .and_then(Path::file_name)
+            .map_or_else(|| "Untitled".to_string(), |name| name.to_string_lossy().into_owned());
+        if self.dirty {
+            format!("{name}*")
+        } else {
+            name
+        }
+    }
+
+    /// Save the palette as a project file, which becomes the current file
+    pub fn save_project(&mut self, path: &Path) -> Result<(), ProjectError> {
+        Project::from_app(self).save(path)?;
+        self.file_path = Some(path.to_path_buf());
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Replace the palette with a project file
+    pub fn open_project(&mut self, path: &Path) -> Result<(), ProjectError> {
+        Project::load(path)?.apply(self);
+        self.file_path = Some(path.to_path_buf());
+        self.dirty = false;
+        Ok(())
     }
 
     /// Add a new swatch to the palette
     pub fn add_swatch(&mut self, swatch: Swatch) {
-        self.palette.swatches.push(swatch);
+        self.mark_dirty();
+        self.palette.add_swatch(swatch);
         self.generated_colors.push(Vec::new());
         // Regenerate colors for the new swatch
         let idx = self.palette.swatches.len() - 1;
-        self.generated_colors[idx] = self.palette.swatches[idx].generate_colors();
+        self.generated_colors[idx] = self.palette.display_colors(idx);
+    }
+
+    /// Insert a swatch at `index` and select it
+    pub fn insert_swatch(&mut self, index: usize, swatch: Swatch) {
+        let index = index.min(self.palette.swatches.len());
+        self.mark_dirty();
+        self.palette.insert_swatch(index, swatch);
+        self.current_swatch_index = index;
+        self.regenerate_all_colors();
+    }
+
+    /// Append the swatches of another palette and select the first of them
+    pub fn append_palette(&mut self, palette: Palette) {
+        if palette.swatches.is_empty() {
+            return;
+        }
+        self.mark_dirty();
+        let first = self.palette.swatches.len();
+        self.palette.append_palette(palette);
+        self.current_swatch_index = first;
+        self.regenerate_all_colors();
+    }
+
+    /// Replace every swatch in the palette
+    pub fn replace_swatches(&mut self, swatches: Vec<Swatch>) {
+        if swatches.is_empty() {
+            return;
+        }
+        self.mark_dirty();
+        self.palette.swatches.clear();
+        for swatch in swatches {
+            self.palette.add_swatch(swatch);
+        }
+        self.current_swatch_index = 0;
+        self.regenerate_all_colors();
+    }
+
+    /// Link (or unlink) a control point of the current swatch
+    pub fn link_control_point(&mut self, point_id: u32, link: Option<ColorLink>) -> Result<(), LinkError> {
+        let swatch_id = self.current_swatch().id();
+        self.palette.set_link(swatch_id, point_id, link)?;
+        self.regenerate_current_colors();
+        Ok(())
     }
 
     /// Remove a swatch from the palette by index
@@ -78,6 +278,7 @@ impl App {
         }
         
         if index < self.palette.swatches.len() {
+            self.mark_dirty();
             self.palette.swatches.remove(index);
             self.generated_colors.remove(index);
             
@@ -85,12 +286,14 @@ impl App {
             if self.current_swatch_index >= self.palette.swatches.len() {
                 self.current_swatch_index = self.palette.swatches.len() - 1;
             }
+            self.clamp_color_selection();
         }
     }
 
     /// Move a swatch up in the list (decrease index)
     pub fn move_swatch_up(&mut self, index: usize) {
         if index > 0 && index < self.palette.swatches.len() {
+            self.mark_dirty();
             self.palette.swatches.swap(index, index - 1);
             self.generated_colors.swap(index, index - 1);
             
@@ -106,6 +309,7 @@ impl App {
     /// Move a swatch down in the list (increase index)
     pub fn move_swatch_down(&mut self, index: usize) {
         if index + 1 < self.palette.swatches.len() {
+            self.mark_dirty();
             self.palette.swatches.swap(index, index + 1);
             self.generated_colors.swap(index, index + 1);
             
@@ -118,18 +322,45 @@ impl App {
         }
     }
 
+    /// Move a swatch to another position, shifting the ones in between
+    pub fn move_swatch(&mut self, from: usize, to: usize) {
+        let len = self.palette.swatches.len();
+        if from >= len || to >= len || from == to {
+            return;
+        }
+
+        self.mark_dirty();
+        let swatch = self.palette.swatches.remove(from);
+        self.palette.swatches.insert(to, swatch);
+        let colors = self.generated_colors.remove(from);
+        self.generated_colors.insert(to, colors);
+
+        // Keep the same swatch selected
+        let current = self.current_swatch_index;
+        self.current_swatch_index = if current == from {
+            to
+        } else if from < current && current <= to {
+            current - 1
+        } else if to <= current && current < from {
+            current + 1
+        } else {
+            current
+        };
+    }
+
     /// Duplicate a swatch
     pub fn duplicate_swatch(&mut self, index: usize) {
         if index < self.palette.swatches.len() {
+            self.mark_dirty();
             let swatch_clone = self.palette.swatches[index].clone();
             
             // Insert after the original
             let insert_idx = index + 1;
-            self.palette.swatches.insert(insert_idx, swatch_clone);
+            self.palette.insert_swatch(insert_idx, swatch_clone);
             self.generated_colors.insert(insert_idx, Vec::new());
             
             // Regenerate colors for the new swatch
-            self.generated_colors[insert_idx] = self.palette.swatches[insert_idx].generate_colors();
+            self.generated_colors[insert_idx] = self.palette.display_colors(insert_idx);
         }
     }
 
@@ -140,6 +371,7 @@ impl App {
             return;
         }
         
+        self.mark_dirty();
         self.palette.swatches.swap(a, b);
         self.generated_colors.swap(a, b);
         
@@ -155,9 +387,42 @@ impl App {
     pub fn select_swatch(&mut self, index: usize) {
         if index < self.palette.swatches.len() {
             self.current_swatch_index = index;
+            self.clamp_color_selection();
+        }
+    }
+
+    /// Animate the viewport to show every swatch
+    pub fn zoom_to_fit(&mut self) {
+        if let Some(bounds) = palette_bounds(&self.layout, &self.generated_colors) {
+            self.viewport.frame(bounds);
         }
     }
 
+    /// Animate the viewport to show the current swatch
+    pub fn frame_current_swatch(&mut self) {
+        if let Some(bounds) = swatch_bounds(&self.layout, &self.generated_colors, self.current_swatch_index) {
+            self.viewport.frame(bounds);
+        }
+    }
+
+    /// Change how swatches are arranged (saved with the project)
+    pub fn set_layout(&mut self, layout: PaletteLayout) {
+        if layout != self.layout {
+            self.layout = layout;
+            self.mark_dirty();
+        }
+    }
+
+    /// Switch to the next layout mode
+    pub fn cycle_layout_mode(&mut self) {
+        let all = LayoutMode::ALL;
+        let current = all.iter().position(|&m| m == self.layout.mode).unwrap_or(0);
+        self.set_layout(PaletteLayout {
+            mode: all[(current + 1) % all.len()],
+            ..self.layout
+        });
+    }
+
     /// Get the number of swatches
     pub fn swatch_count(&self) -> usize {
         self.palette.swatches.len()
@@ -0,0 +1,147 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::app::App;
+use crate::project::{write_atomically, Project};
+use crate::settings::{data_dir, Settings};
+
+// =============================================================================
+// Autosave and crash recovery
+// =============================================================================
+//
+// While the editor runs, a lock file sits in the data directory and the
+// project is periodically written to a recovery file. A clean exit removes
+// both. Each running instance has its own pair, named by its process ID, and
+// holds an OS lock on its lock file until it exits. A lock file nobody holds
+// at startup means that session ended without cleaning up, so its recovery
+// file is offered for restore; sessions still running are left alone.
+
+fn lock_path(dir: &Path, session: u32) -> PathBuf {
+    dir.join(format!("session-{session}.lock"))
+}
+
+fn recovery_path(dir: &Path, session: u32) -> PathBuf {
+    dir.join(format!("recovery-{session}.json"))
+}
+
+/// Sessions whose lock file is no longer held by a running instance
+fn stale_sessions(dir: &Path) -> Vec<u32> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let session = name.to_str()?.strip_prefix("session-")?.strip_suffix(".lock")?.parse().ok()?;
+            // Taking the lock only succeeds once its owner is gone; it's
+            // released again when `file` is dropped
+            let file = File::open(entry.path()).ok()?;
+            file.try_lock().ok()?;
+            Some(session)
+        })
+        .collect()
+}
+
+pub struct Autosave {
+    /// None if there is nowhere to save to
+    dir: Option<PathBuf>,
+    /// This instance's session ID (its process ID)
+    session: u32,
+    /// Held open and locked for as long as the session runs
+    lock: Option<File>,
+    /// The crashed session whose recovery file was offered at startup,
+    /// cleaned up with this one
+    offered: Option<u32>,
+    last_save_time: f64,
+    /// The last recovery file written, to skip saving unchanged state
+    last_saved: Option<String>,
+    /// The last autosave failure, if the most recent attempt failed
+    pub error: Option<String>,
+}
+
+impl Autosave {
+    /// Begin a session at time `now` (seconds). Returns the project left by
+    /// the most recent session that exited uncleanly. Older crashed sessions
+    /// keep their recovery files and are offered by the following starts.
+    pub fn start(now: f64) -> (Self, Option<Project>) {
+        let dir = data_dir().filter(|dir| fs::create_dir_all(dir).is_ok());
+        let session = std::process::id();
+
+        let mut offered = None;
+        let mut recovered = None;
+        if let Some(dir) = &dir {
+            // Sessions that crashed before their first autosave left nothing to restore
+            let mut stale: Vec<(u32, SystemTime)> = Vec::new();
+            for session in stale_sessions(dir) {
+                match fs::metadata(recovery_path(dir, session)).and_then(|m| m.modified()) {
+                    Ok(modified) => stale.push((session, modified)),
+                    Err(_) => {
+                        let _ = fs::remove_file(lock_path(dir, session));
+                    }
+                }
+            }
+            if let Some(&(latest, _)) = stale.iter().max_by_key(|(_, modified)| *modified) {
+                offered = Some(latest);
+                recovered = Project::load(recovery_path(dir, latest)).ok();
+            }
+        }
+
+        let lock = dir.as_ref().and_then(|dir| {
+            let file = File::create(lock_path(dir, session)).ok()?;
+            file.lock().ok()?;
+            Some(file)
+        });
+
+        let autosave = Self {
+            dir,
+            session,
+            lock,
+            offered,
+            last_save_time: now,
+            last_saved: None,
+            error: None,
+        };
+        (autosave, recovered)
+    }
+
+    /// Save the app to the recovery file if the interval has passed and it changed
+    pub fn update(&mut self, app: &App, settings: &Settings, now: f64) {
+        if !settings.autosave_enabled || now - self.last_save_time < settings.autosave_interval as f64 {
+            return;
+        }
+        self.last_save_time = now;
+        self.save(app);
+    }
+
+    fn save(&mut self, app: &App) {
+        let Some(dir) = &self.dir else { return };
+        let json = match Project::from_app(app).to_json() {
+            Ok(json) => json,
+            Err(err) => {
+                self.error = Some(err.to_string());
+                return;
+            }
+        };
+        if self.last_saved.as_ref() == Some(&json) {
+            return;
+        }
+        match write_atomically(&recovery_path(dir, self.session), &json) {
+            Ok(()) => {
+                self.last_saved = Some(json);
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    /// End the session cleanly, so the next start doesn't offer a restore.
+    /// The crashed session offered at startup goes too.
+    pub fn finish(self) {
+        let Some(dir) = &self.dir else { return };
+        drop(self.lock);
+        for session in self.offered.into_iter().chain([self.session]) {
+            let _ = fs::remove_file(recovery_path(dir, session));
+            let _ = fs::remove_file(lock_path(dir, session));
+        }
+    }
+}
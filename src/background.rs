@@ -0,0 +1,177 @@
+use egui_macroquad::egui::Color32;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::color::oklab_to_rgb;
+use crate::image_io::SourceImage;
+use crate::rendering::draw_checker_background;
+use crate::viewport::Viewport;
+
+// =============================================================================
+// Canvas backgrounds
+// =============================================================================
+//
+// Colors are judged against their surroundings, so the canvas behind the
+// palette can be a flat neutral, a custom color or an image (a screenshot of
+// the game or UI the palette is for). The comparison window shows the
+// swatches over several reference backgrounds at once.
+
+/// What the canvas is drawn over
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BackgroundKind {
+    /// The dark parallax checker
+    #[default]
+    Checker,
+    /// Neutral grey of a chosen OkLab lightness
+    Grey,
+    White,
+    Black,
+    Custom,
+    /// An image file, scaled to cover the window
+    Image,
+}
+
+impl BackgroundKind {
+    pub const ALL: &'static [BackgroundKind] = &[
+        BackgroundKind::Checker,
+        BackgroundKind::Grey,
+        BackgroundKind::White,
+        BackgroundKind::Black,
+        BackgroundKind::Custom,
+        BackgroundKind::Image,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackgroundKind::Checker => "Checker",
+            BackgroundKind::Grey => "Neutral Grey",
+            BackgroundKind::White => "White",
+            BackgroundKind::Black => "Black",
+            BackgroundKind::Custom => "Custom Color",
+            BackgroundKind::Image => "Image",
+        }
+    }
+}
+
+/// Background preferences, saved with the settings
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackgroundSettings {
+    pub kind: BackgroundKind,
+    /// OkLab lightness of the neutral grey, 0..1
+    pub grey_lightness: f32,
+    pub custom_color: Color32,
+    pub image_path: String,
+    /// Surfaces shown side by side in the comparison window
+    pub comparison: Vec<Color32>,
+}
+
+impl Default for BackgroundSettings {
+    fn default() -> Self {
+        Self {
+            kind: BackgroundKind::default(),
+            grey_lightness: 0.5,
+            custom_color: Color32::from_rgb(40, 44, 52),
+            image_path: String::new(),
+            comparison: default_comparison_backgrounds(),
+        }
+    }
+}
+
+impl BackgroundSettings {
+    /// The flat color for the solid kinds
+    pub fn fill_color(&self) -> Option<Color32> {
+        match self.kind {
+            BackgroundKind::Grey => Some(neutral_grey(self.grey_lightness)),
+            BackgroundKind::White => Some(Color32::WHITE),
+            BackgroundKind::Black => Some(Color32::BLACK),
+            BackgroundKind::Custom => Some(self.custom_color),
+            BackgroundKind::Checker | BackgroundKind::Image => None,
+        }
+    }
+}
+
+/// Grey with the given OkLab lightness
+pub fn neutral_grey(lightness: f32) -> Color32 {
+    oklab_to_rgb(lightness.clamp(0.0, 1.0), 0.0, 0.0)
+}
+
+/// White, three greys and black: light UI to dark UI
+pub fn default_comparison_backgrounds() -> Vec<Color32> {
+    vec![
+        Color32::WHITE,
+        neutral_grey(0.8),
+        neutral_grey(0.55),
+        neutral_grey(0.3),
+        Color32::BLACK,
+    ]
+}
+
+/// Texture for `BackgroundKind::Image`, reloaded when the path changes
+#[derive(Default)]
+pub struct BackgroundImage {
+    path: String,
+    texture: Option<Texture2D>,
+    /// Why the image couldn't be loaded
+    pub error: Option<String>,
+}
+
+impl BackgroundImage {
+    /// Load the image named by the settings, if it isn't loaded already
+    pub fn sync(&mut self, settings: &BackgroundSettings) {
+        if settings.kind != BackgroundKind::Image || settings.image_path == self.path {
+            return;
+        }
+        self.path = settings.image_path.clone();
+        self.texture = None;
+        self.error = None;
+        if self.path.trim().is_empty() {
+            return;
+        }
+        match SourceImage::load(self.path.trim()) {
+            Ok(image) => match (u16::try_from(image.width), u16::try_from(image.height)) {
+                (Ok(width), Ok(height)) => {
+                    self.texture = Some(Texture2D::from_rgba8(width, height, &image.to_rgba_bytes()));
+                }
+                _ => self.error = Some("Image is too large".to_string()),
+            },
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+}
+
+/// Draw the canvas background chosen in the settings
+pub fn draw_background(viewport: &Viewport, settings: &BackgroundSettings, image: &BackgroundImage) {
+    if let Some(color) = settings.fill_color() {
+        let [r, g, b, _] = color.to_srgba_unmultiplied();
+        clear_background(Color::from_rgba(r, g, b, 255));
+        return;
+    }
+    match (&image.texture, settings.kind) {
+        (Some(texture), BackgroundKind::Image) => {
+            // Scale to cover the window, centered
+            let size = texture.size();
+            let scale = (screen_width() / size.x).max(screen_height() / size.y);
+            let dest = size * scale;
+            clear_background(BLACK);
+            draw_texture_ex(
+                texture,
+                (screen_width() - dest.x) / 2.0,
+                (screen_height() - dest.y) / 2.0,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(dest),
+                    ..Default::default()
+                },
+            );
+        }
+        // Also the fallback while no image is loaded
+        _ => {
+            draw_checker_background(viewport);
+            if let (Some(error), BackgroundKind::Image) = (&image.error, settings.kind) {
+                let message = format!("Background image: {error}");
+                draw_text(&message, 12.0, screen_height() - 12.0, 18.0, Color::new(0.8, 0.4, 0.4, 1.0));
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::color::{parse_hex_color, ColorSpace};
+use crate::curves::CurveType;
+use crate::gradient_map::{gradient_map, GradientMapInput, GradientSource};
+use crate::palette::{ControlPoint, Swatch};
+use crate::project::Project;
+
+// =============================================================================
+// Headless command line interface
+// =============================================================================
+//
+// When the binary is started with arguments, it runs one command and exits
+// without opening a window. This makes the palette tools usable from build
+// scripts and asset pipelines.
+
+const USAGE: &str = "\
+Usage:
+  chrmapal                      Start the editor
+  chrmapal gradient-map <input.png> <output.png> --colors <#hex,#hex,...> [options]
+  chrmapal gradient-map <input.png> <output.png> --project <file> [--swatch <name|n>] [options]
+
+Gradient map options:
+  --colors <list>   Control point colors, bright first, evenly spaced
+  --size <n>        Number of swatch steps (default 8)
+  --space <name>    Interpolation color space: RGB, OkLab or OkLCh (default RGB)
+  --project <file>  Map through a swatch of a saved project instead of --colors
+  --swatch <name|n> Swatch of the project, by name or number from 1
+                    (default: the swatch selected when it was saved)
+  --index           Map palette indices of an indexed PNG instead of luminance
+  --invert          Map the first level to the last swatch color";
+
+/// Run a headless command, returning the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let result = match args.first().map(String::as_str) {
+        Some("gradient-map") => run_gradient_map(&args[1..]),
+        Some("help" | "--help" | "-h") => {
+            println!("{USAGE}");
+            return 0;
+        }
+        Some(other) => Err(format!("Unknown command '{other}'")),
+        None => Err("No command given".to_string()),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            1
+        }
+    }
+}
+
+fn run_gradient_map(args: &[String]) -> Result<(), String> {
+    let mut positional = Vec::new();
+    let mut colors = None;
+    let mut size = None;
+    let mut space = None;
+    let mut project = None;
+    let mut swatch_name = None;
+    let mut source = GradientSource::Luminance;
+    let mut invert = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or(format!("Missing value for {arg}"));
+        match arg.as_str() {
+            "--colors" => colors = Some(value()?.clone()),
+            "--size" => size = Some(value()?.parse().map_err(|_| "Invalid --size".to_string())?),
+            "--space" => {
+                let name = value()?;
+                space = Some(ColorSpace::from_name(name).ok_or(format!("Unknown color space '{name}'"))?);
+            }
+            "--project" => project = Some(value()?.clone()),
+            "--swatch" => swatch_name = Some(value()?.clone()),
+            "--index" => source = GradientSource::Index,
+            "--invert" => invert = true,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option '{arg}'")),
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    let [input_path, output_path] = positional[..] else {
+        return Err("Expected an input and an output path".to_string());
+    };
+    let colors = match (project, colors) {
+        (Some(path), None) => {
+            if size.is_some() || space.is_some() {
+                return Err("--size and --space can't be combined with --project".to_string());
+            }
+            let project = Project::load(&path).map_err(|err| format!("Couldn't open {path}: {err}"))?;
+            let index = find_swatch(&project, swatch_name.as_deref())?;
+            project.palette.display_colors(index)
+        }
+        (None, Some(colors)) => {
+            if swatch_name.is_some() {
+                return Err("--swatch requires --project".to_string());
+            }
+            swatch_from_hex_list(&colors, size.unwrap_or(8), space.unwrap_or_default())?.generate_colors()
+        }
+        (Some(_), Some(_)) => return Err("Use either --colors or --project, not both".to_string()),
+        (None, None) => return Err("Missing --colors or --project".to_string()),
+    };
+
+    let input = GradientMapInput::load(input_path, source)?;
+    gradient_map(&input, &colors, invert)
+        .save(output_path)
+        .map_err(|err| err.to_string())
+}
+
+/// The index of the project swatch called `name`, or numbered `name` from 1;
+/// without a name, the swatch that was selected when the project was saved
+fn find_swatch(project: &Project, name: Option<&str>) -> Result<usize, String> {
+    let palette = &project.palette;
+    if palette.swatches.is_empty() {
+        return Err("The project has no swatches".to_string());
+    }
+    let Some(name) = name else {
+        return Ok(project.current_swatch_index.min(palette.swatches.len() - 1));
+    };
+    palette
+        .swatches
+        .iter()
+        .enumerate()
+        .position(|(i, swatch)| swatch.display_name(i) == name.trim())
+        .or_else(|| {
+            name.parse::<usize>()
+                .ok()
+                .filter(|n| (1..=palette.swatches.len()).contains(n))
+                .map(|n| n - 1)
+        })
+        .ok_or(format!("No swatch '{name}' in the project"))
+}
+
+/// Build a swatch with evenly spaced control points from a comma-separated hex list
+fn swatch_from_hex_list(list: &str, size: usize, space: ColorSpace) -> Result<Swatch, String> {
+    let colors = list
+        .split(',')
+        .map(|hex| parse_hex_color(hex).ok_or(format!("Invalid color '{hex}'")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let points = colors
+        .iter()
+        .enumerate()
+        .map(|(i, &color)| {
+            let position = if colors.len() > 1 {
+                i as f32 / (colors.len() - 1) as f32
+            } else {
+                0.5
+            };
+            ControlPoint::new(i as u32, position, color)
+        })
+        .collect();
+
+    Ok(Swatch::new(size.max(1), points, CurveType::default(), space))
+}
@@ -0,0 +1,246 @@
+use egui_macroquad::egui::Color32;
+use macroquad::miniquad::window::{clipboard_get, clipboard_set};
+use serde::{Deserialize, Serialize};
+
+use crate::app::App;
+use crate::color::{oklch_to_rgb, parse_hex_color, rgb_to_oklch, with_alpha};
+use crate::export::hex;
+use crate::fit::{fit_swatch, DEFAULT_FIT_TOLERANCE};
+use crate::palette::{Palette, Swatch};
+
+// =============================================================================
+// Clipboard: copy and paste colors, swatches and palettes as text
+// =============================================================================
+//
+// Single colors and hex lists are plain text, so they can be exchanged with
+// any other application. Whole swatches and palettes are JSON tagged with a
+// "chrmapal" field, keeping control points, curves and links intact.
+
+/// Text notation for a copied color
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ColorFormat {
+    /// `#rrggbb`, or `#rrggbbaa` when translucent
+    #[default]
+    Hex,
+    /// CSS `rgb(r, g, b)`
+    Rgb,
+    /// CSS `oklch(L% C H)`
+    Oklch,
+}
+
+impl ColorFormat {
+    pub const ALL: &'static [ColorFormat] = &[ColorFormat::Hex, ColorFormat::Rgb, ColorFormat::Oklch];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorFormat::Hex => "Hex",
+            ColorFormat::Rgb => "rgb()",
+            ColorFormat::Oklch => "oklch()",
+        }
+    }
+
+    /// Translucent colors keep their alpha: `#rrggbbaa`, or `/ alpha` in the
+    /// CSS functions
+    pub fn format(&self, color: Color32) -> String {
+        let alpha = color.a() as f32 / 255.0;
+        match self {
+            ColorFormat::Hex => hex(color),
+            ColorFormat::Rgb => {
+                let [r, g, b, _] = color.to_srgba_unmultiplied();
+                if color.is_opaque() {
+                    format!("rgb({r}, {g}, {b})")
+                } else {
+                    format!("rgb({r} {g} {b} / {alpha:.3})")
+                }
+            }
+            ColorFormat::Oklch => {
+                let (l, c, h) = rgb_to_oklch(color);
+                let text = format!("oklch({:.1}% {:.3} {:.1}", l * 100.0, c, h);
+                if color.is_opaque() {
+                    text + ")"
+                } else {
+                    format!("{text} / {alpha:.3})")
+                }
+            }
+        }
+    }
+}
+
+/// Swatches and palettes on the clipboard
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "chrmapal", rename_all = "snake_case")]
+enum ClipboardData {
+    Swatch { swatch: Swatch },
+    Palette { palette: Palette },
+}
+
+/// What a piece of clipboard text turned out to be
+#[derive(Clone, Debug)]
+pub enum Pasted {
+    Color(Color32),
+    /// A list of two or more hex colors
+    Colors(Vec<Color32>),
+    Swatch(Swatch),
+    Palette(Palette),
+}
+
+/// Parse a color in any of the `ColorFormat` notations
+pub fn parse_color(text: &str) -> Option<Color32> {
+    let text = text.trim();
+    let lower = text.to_ascii_lowercase();
+    let (function, arguments) = match lower.split_once('(') {
+        Some((function, rest)) => (function.trim(), rest.strip_suffix(')')?),
+        None => return parse_hex_color(text),
+    };
+    // `rgb(1, 2, 3)`, `rgba(1, 2, 3, 0.5)` or `rgb(1 2 3 / 50%)`
+    let arguments = arguments.replacen('/', " ", 1);
+    let values: Vec<&str> = arguments
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .collect();
+    let (first, second, third, alpha) = match values[..] {
+        [first, second, third] => (first, second, third, 255),
+        [first, second, third, alpha] => {
+            let amount = match alpha.strip_suffix('%') {
+                Some(percent) => percent.parse::<f32>().ok()? / 100.0,
+                None => alpha.parse::<f32>().ok()?,
+            };
+            (first, second, third, (amount.clamp(0.0, 1.0) * 255.0).round() as u8)
+        }
+        _ => return None,
+    };
+
+    let color = match function {
+        "rgb" | "rgba" => {
+            let channel = |value: &str| -> Option<u8> {
+                let amount = match value.strip_suffix('%') {
+                    Some(percent) => percent.parse::<f32>().ok()? * 2.55,
+                    None => value.parse::<f32>().ok()?,
+                };
+                Some(amount.round().clamp(0.0, 255.0) as u8)
+            };
+            Color32::from_rgb(channel(first)?, channel(second)?, channel(third)?)
+        }
+        "oklch" => {
+            let l = match first.strip_suffix('%') {
+                Some(percent) => percent.parse::<f32>().ok()? / 100.0,
+                None => first.parse::<f32>().ok()?,
+            };
+            // CSS maps 100% chroma to 0.4
+            let c = match second.strip_suffix('%') {
+                Some(percent) => percent.parse::<f32>().ok()? / 100.0 * 0.4,
+                None => second.parse::<f32>().ok()?,
+            };
+            let h = third.trim_end_matches("deg").parse::<f32>().ok()?;
+            oklch_to_rgb(l, c, h)
+        }
+        _ => return None,
+    };
+    Some(with_alpha(color, alpha))
+}
+
+/// Interpret clipboard text: a single color, a hex list, or a copied swatch or palette
+pub fn parse_clipboard(text: &str) -> Option<Pasted> {
+    let text = text.trim();
+    if text.starts_with('{') {
+        return match serde_json::from_str(text).ok()? {
+            ClipboardData::Swatch { swatch } => Some(Pasted::Swatch(swatch)),
+            ClipboardData::Palette { palette } => Some(Pasted::Palette(palette)),
+        };
+    }
+    if let Some(color) = parse_color(text) {
+        return Some(Pasted::Color(color));
+    }
+    let colors = text
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .filter(|token| !token.is_empty())
+        .map(parse_hex_color)
+        .collect::<Option<Vec<_>>>()?;
+    (colors.len() > 1).then_some(Pasted::Colors(colors))
+}
+
+/// One hex color per line
+pub fn hex_list(colors: &[Color32]) -> String {
+    colors.iter().map(|&color| hex(color)).collect::<Vec<_>>().join("\n")
+}
+
+pub fn swatch_text(swatch: &Swatch) -> String {
+    let data = ClipboardData::Swatch { swatch: swatch.clone() };
+    serde_json::to_string_pretty(&data).unwrap_or_default()
+}
+
+pub fn palette_text(palette: &Palette) -> String {
+    let data = ClipboardData::Palette { palette: palette.clone() };
+    serde_json::to_string_pretty(&data).unwrap_or_default()
+}
+
+// =============================================================================
+// Copy and paste
+// =============================================================================
+
+/// Copy the selected color of the current swatch
+pub fn copy_selected_color(app: &App, format: ColorFormat) {
+    let color = app
+        .generated_colors
+        .get(app.current_swatch_index)
+        .and_then(|colors| colors.get(app.selected_color_index));
+    if let Some(&color) = color {
+        clipboard_set(&format.format(color));
+    }
+}
+
+/// Copy the generated colors of the current swatch as a hex list
+pub fn copy_swatch_colors(app: &App) {
+    if let Some(colors) = app.generated_colors.get(app.current_swatch_index) {
+        clipboard_set(&hex_list(colors));
+    }
+}
+
+/// Copy the current swatch with its control points and curve
+pub fn copy_swatch(app: &App) {
+    clipboard_set(&swatch_text(app.current_swatch()));
+}
+
+pub fn copy_palette(app: &App) {
+    clipboard_set(&palette_text(&app.palette));
+}
+
+/// Paste the system clipboard. Returns false if it held nothing usable.
+pub fn paste(app: &mut App) -> bool {
+    clipboard_get().is_some_and(|text| paste_text(app, &text))
+}
+
+/// Paste clipboard text into the palette:
+/// - a single color is pinned at the selected step of the current swatch
+/// - a hex list becomes a new swatch, with control points fitted to it
+/// - a copied swatch is inserted after the current one
+/// - a copied palette has its swatches appended
+///
+/// Returns false if the text wasn't recognized, or held a swatch or palette
+/// the editor couldn't have made (see `Swatch::is_valid`).
+pub fn paste_text(app: &mut App, text: &str) -> bool {
+    let Some(pasted) = parse_clipboard(text) else {
+        return false;
+    };
+    let valid = match &pasted {
+        Pasted::Swatch(swatch) => swatch.is_valid(),
+        Pasted::Palette(palette) => palette.is_valid(),
+        Pasted::Color(_) | Pasted::Colors(_) => true,
+    };
+    if !valid {
+        return false;
+    }
+    match pasted {
+        Pasted::Color(color) => app.pin_color(app.selected_color_index, color),
+        Pasted::Colors(colors) => {
+            let swatch = fit_swatch(&colors, app.current_swatch().color_space, DEFAULT_FIT_TOLERANCE);
+            app.insert_swatch(app.current_swatch_index + 1, swatch);
+        }
+        Pasted::Swatch(mut swatch) => {
+            swatch.unlink_control_points();
+            app.insert_swatch(app.current_swatch_index + 1, swatch);
+        }
+        Pasted::Palette(palette) => app.append_palette(palette),
+    }
+    true
+}
@@ -1,10 +1,11 @@
 use egui_macroquad::egui::Color32;
+use serde::{Deserialize, Serialize};
 
 // =============================================================================
 // Color Space Enum - selectable at runtime
 // =============================================================================
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum ColorSpace {
     #[default]
     Rgb,
@@ -22,6 +23,129 @@ impl ColorSpace {
             ColorSpace::OkLCh => "OkLCh",
         }
     }
+
+    /// Look up a color space by its display name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|space| space.name().eq_ignore_ascii_case(name))
+    }
+}
+
+// =============================================================================
+// Float colors
+// =============================================================================
+//
+// The palette model and the generator work on `Srgba`: sRGB-encoded f32
+// channels with straight alpha. Colors are only quantized to 8 bits
+// (`Color32`) for display and for formats that store bytes, so chained
+// conversions and interpolation don't accumulate rounding errors.
+
+/// sRGB color with straight (unmultiplied) alpha, channels in [0.0, 1.0].
+/// Generated colors may lie outside that range (extended sRGB) until they
+/// are mapped into the palette's output gamut.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "SrgbaRepr")]
+pub struct Srgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+/// Colors in files are either float channels or, in files written before
+/// the float pipeline, an egui `Color32`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SrgbaRepr {
+    Channels { r: f32, g: f32, b: f32, a: f32 },
+    Color32(Color32),
+}
+
+impl From<SrgbaRepr> for Srgba {
+    fn from(repr: SrgbaRepr) -> Self {
+        match repr {
+            SrgbaRepr::Channels { r, g, b, a } => Srgba::new(r, g, b, a),
+            SrgbaRepr::Color32(color) => color.into(),
+        }
+    }
+}
+
+impl Srgba {
+    pub const BLACK: Srgba = Srgba::new(0.0, 0.0, 0.0, 1.0);
+    pub const WHITE: Srgba = Srgba::new(1.0, 1.0, 1.0, 1.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        self.a >= 1.0
+    }
+
+    pub fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+
+    /// 8-bit color, rounded as asked
+    pub fn quantize(&self, rounding: Rounding) -> Color32 {
+        let [r, g, b, a] = [self.r, self.g, self.b, self.a].map(|x| rounding.apply(x, 255.0) as u8);
+        Color32::from_rgba_unmultiplied(r, g, b, a)
+    }
+
+    /// Linear-light RGB, for shaders and HDR pipelines
+    pub fn to_linear(self) -> [f32; 3] {
+        [self.r, self.g, self.b].map(srgb_to_linear)
+    }
+}
+
+impl From<Color32> for Srgba {
+    fn from(color: Color32) -> Self {
+        let [r, g, b, a] = color.to_srgba_unmultiplied().map(|x| x as f32 / 255.0);
+        Srgba::new(r, g, b, a)
+    }
+}
+
+impl From<Srgba> for Color32 {
+    fn from(color: Srgba) -> Self {
+        color.quantize(Rounding::Nearest)
+    }
+}
+
+/// How float channels are quantized to integers
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Rounding {
+    #[default]
+    Nearest,
+    /// Toward zero, which is how colors were truncated in older versions
+    Down,
+    Up,
+}
+
+impl Rounding {
+    pub const ALL: &'static [Rounding] = &[Rounding::Nearest, Rounding::Down, Rounding::Up];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rounding::Nearest => "Nearest",
+            Rounding::Down => "Down",
+            Rounding::Up => "Up",
+        }
+    }
+
+    /// Scale a [0, 1] channel to [0, max] and round it. Values within a
+    /// thousandth of a level count as exact, so float noise doesn't push
+    /// them to the next level when rounding up or down.
+    pub fn apply(&self, x: f32, max: f32) -> f32 {
+        let value = x.clamp(0.0, 1.0) * max;
+        match self {
+            Rounding::Nearest => value.round(),
+            Rounding::Down => (value + 1e-3).floor(),
+            Rounding::Up => (value - 1e-3).ceil(),
+        }
+        .clamp(0.0, max)
+    }
 }
 
 // =============================================================================
@@ -31,81 +155,94 @@ impl ColorSpace {
 /// Interpolate between two colors in the specified color space.
 /// `t` is in [0.0, 1.0], where t=0 returns c1 and t=1 returns c2.
 pub fn lerp_color(c1: Color32, c2: Color32, t: f32, space: ColorSpace) -> Color32 {
+    lerp_srgba(c1.into(), c2.into(), t, t, space).into()
+}
+
+/// Interpolate color and alpha separately, so alpha can follow its own curve.
+///
+/// Colors are interpolated premultiplied: a more transparent end contributes
+/// proportionally less of its color, so fading to a transparent color doesn't
+/// pass through that color's (invisible) hue. Hue in OkLCh follows the same
+/// weighting, which keeps it on the shortest path. The weighting uses the
+/// color's `t`; `alpha_t` only sets the alpha of the result.
+pub fn lerp_srgba(c1: Srgba, c2: Srgba, t: f32, alpha_t: f32, space: ColorSpace) -> Srgba {
     let t = t.clamp(0.0, 1.0);
-    
-    match space {
-        ColorSpace::Rgb => lerp_rgb(c1, c2, t),
-        ColorSpace::OkLab => lerp_oklab(c1, c2, t),
-        ColorSpace::OkLCh => lerp_oklch(c1, c2, t),
-    }
+    let alpha_t = alpha_t.clamp(0.0, 1.0);
+
+    // Lerping premultiplied coordinates and dividing by the lerped alpha is a
+    // plain lerp of the unmultiplied coordinates at a reweighted t
+    let weight = lerp_f32(c1.a, c2.a, t);
+    let color_t = if weight > 0.0 { t * c2.a / weight } else { t };
+
+    let color = match space {
+        ColorSpace::Rgb => lerp_rgb(c1, c2, color_t),
+        ColorSpace::OkLab => lerp_oklab(c1, c2, color_t),
+        ColorSpace::OkLCh => lerp_oklch(c1, c2, color_t),
+    };
+    color.with_alpha(lerp_f32(c1.a, c2.a, alpha_t))
 }
 
 /// Extrapolate a color from a single reference point.
 /// `direction` indicates how far and in which direction to shift:
 /// - direction < 0: shift toward black/darker
 /// - direction > 0: shift toward white/lighter
-/// The magnitude determines how much to shift.
-pub fn extrapolate_color(reference: Color32, direction: f32, space: ColorSpace) -> Color32 {
-    match space {
+/// The magnitude determines how much to shift. Alpha is kept.
+pub fn extrapolate_srgba(reference: Srgba, direction: f32, space: ColorSpace) -> Srgba {
+    let color = match space {
         ColorSpace::Rgb => extrapolate_rgb(reference, direction),
         ColorSpace::OkLab => extrapolate_oklab(reference, direction),
         ColorSpace::OkLCh => extrapolate_oklch(reference, direction),
-    }
+    };
+    color.with_alpha(reference.a)
+}
+
+/// The same color with a different alpha
+pub fn with_alpha(color: Color32, alpha: u8) -> Color32 {
+    let [r, g, b, _] = color.to_srgba_unmultiplied();
+    Color32::from_rgba_unmultiplied(r, g, b, alpha)
 }
 
 // =============================================================================
 // RGB Interpolation
 // =============================================================================
 
-fn lerp_rgb(c1: Color32, c2: Color32, t: f32) -> Color32 {
-    let r = lerp_f32(c1.r() as f32, c2.r() as f32, t);
-    let g = lerp_f32(c1.g() as f32, c2.g() as f32, t);
-    let b = lerp_f32(c1.b() as f32, c2.b() as f32, t);
-    let a = lerp_f32(c1.a() as f32, c2.a() as f32, t);
-    
-    Color32::from_rgba_unmultiplied(
-        r.round() as u8,
-        g.round() as u8,
-        b.round() as u8,
-        a.round() as u8,
+fn lerp_rgb(c1: Srgba, c2: Srgba, t: f32) -> Srgba {
+    Srgba::new(
+        lerp_f32(c1.r, c2.r, t),
+        lerp_f32(c1.g, c2.g, t),
+        lerp_f32(c1.b, c2.b, t),
+        1.0,
     )
 }
 
-fn extrapolate_rgb(reference: Color32, direction: f32) -> Color32 {
+fn extrapolate_rgb(reference: Srgba, direction: f32) -> Srgba {
     // For RGB, we shift toward black (direction < 0) or white (direction > 0)
-    let target = if direction < 0.0 {
-        Color32::BLACK
-    } else {
-        Color32::WHITE
-    };
+    let target = if direction < 0.0 { 0.0 } else { 1.0 };
     
     // Use absolute value as interpolation factor, but can go beyond 1.0
     let t = direction.abs();
+    let channel = |x: f32| lerp_f32(x, target, t).clamp(0.0, 1.0);
     
-    let r = lerp_f32(reference.r() as f32, target.r() as f32, t).clamp(0.0, 255.0);
-    let g = lerp_f32(reference.g() as f32, target.g() as f32, t).clamp(0.0, 255.0);
-    let b = lerp_f32(reference.b() as f32, target.b() as f32, t).clamp(0.0, 255.0);
-    
-    Color32::from_rgb(r.round() as u8, g.round() as u8, b.round() as u8)
+    Srgba::new(channel(reference.r), channel(reference.g), channel(reference.b), 1.0)
 }
 
 // =============================================================================
 // OkLab Interpolation
 // =============================================================================
 
-fn lerp_oklab(c1: Color32, c2: Color32, t: f32) -> Color32 {
-    let (l1, a1, b1) = rgb_to_oklab(c1);
-    let (l2, a2, b2) = rgb_to_oklab(c2);
+fn lerp_oklab(c1: Srgba, c2: Srgba, t: f32) -> Srgba {
+    let (l1, a1, b1) = srgba_to_oklab(c1);
+    let (l2, a2, b2) = srgba_to_oklab(c2);
     
     let l = lerp_f32(l1, l2, t);
     let a = lerp_f32(a1, a2, t);
     let b = lerp_f32(b1, b2, t);
     
-    oklab_to_rgb(l, a, b)
+    oklab_to_srgba(l, a, b)
 }
 
-fn extrapolate_oklab(reference: Color32, direction: f32) -> Color32 {
-    let (l, a, b) = rgb_to_oklab(reference);
+fn extrapolate_oklab(reference: Srgba, direction: f32) -> Srgba {
+    let (l, a, b) = srgba_to_oklab(reference);
     
     // Shift lightness based on direction, keep a and b (chromatic components)
     let new_l = if direction < 0.0 {
@@ -116,16 +253,16 @@ fn extrapolate_oklab(reference: Color32, direction: f32) -> Color32 {
         (l + direction).clamp(0.0, 1.0)
     };
     
-    oklab_to_rgb(new_l, a, b)
+    oklab_to_srgba(new_l, a, b)
 }
 
 // =============================================================================
 // OkLCh Interpolation (perceptually uniform with hue interpolation)
 // =============================================================================
 
-fn lerp_oklch(c1: Color32, c2: Color32, t: f32) -> Color32 {
-    let (l1, c1_chroma, h1) = rgb_to_oklch(c1);
-    let (l2, c2_chroma, h2) = rgb_to_oklch(c2);
+fn lerp_oklch(c1: Srgba, c2: Srgba, t: f32) -> Srgba {
+    let (l1, c1_chroma, h1) = srgba_to_oklch(c1);
+    let (l2, c2_chroma, h2) = srgba_to_oklch(c2);
     
     let l = lerp_f32(l1, l2, t);
     let c = lerp_f32(c1_chroma, c2_chroma, t);
@@ -133,11 +270,11 @@ fn lerp_oklch(c1: Color32, c2: Color32, t: f32) -> Color32 {
     // Interpolate hue on the shortest path around the circle
     let h = lerp_hue(h1, h2, t);
     
-    oklch_to_rgb(l, c, h)
+    oklch_to_srgba(l, c, h)
 }
 
-fn extrapolate_oklch(reference: Color32, direction: f32) -> Color32 {
-    let (l, c, h) = rgb_to_oklch(reference);
+fn extrapolate_oklch(reference: Srgba, direction: f32) -> Srgba {
+    let (l, c, h) = srgba_to_oklch(reference);
     
     // Shift lightness, preserve chroma and hue
     let new_l = if direction < 0.0 {
@@ -146,7 +283,7 @@ fn extrapolate_oklch(reference: Color32, direction: f32) -> Color32 {
         (l + direction).clamp(0.0, 1.0)
     };
     
-    oklch_to_rgb(new_l, c, h)
+    oklch_to_srgba(new_l, c, h)
 }
 
 /// Interpolate hue angles, taking the shortest path around the circle
@@ -178,31 +315,48 @@ fn lerp_hue(h1: f32, h2: f32, t: f32) -> f32 {
 // sRGB <-> Linear RGB conversion
 // =============================================================================
 
-fn srgb_to_linear(x: f32) -> f32 {
-    if x <= 0.04045 {
-        x / 12.92
+/// Decode one sRGB channel in [0, 1] to linear light
+pub fn srgb_to_linear(x: f32) -> f32 {
+    // Mirrored below zero, so extended (out of gamut) values round-trip
+    let magnitude = x.abs();
+    let linear = if magnitude <= 0.04045 {
+        magnitude / 12.92
     } else {
-        ((x + 0.055) / 1.055).powf(2.4)
-    }
+        ((magnitude + 0.055) / 1.055).powf(2.4)
+    };
+    linear.copysign(x)
 }
 
-fn linear_to_srgb(x: f32) -> f32 {
-    if x <= 0.0031308 {
-        x * 12.92
+/// Encode one linear-light channel as sRGB. Values outside [0, 1] stay
+/// outside, for extended sRGB.
+pub fn linear_to_srgb(x: f32) -> f32 {
+    let magnitude = x.abs();
+    let encoded = if magnitude <= 0.0031308 {
+        magnitude * 12.92
     } else {
-        1.055 * x.powf(1.0 / 2.4) - 0.055
-    }
+        1.055 * magnitude.powf(1.0 / 2.4) - 0.055
+    };
+    encoded.copysign(x)
 }
 
 // =============================================================================
 // RGB <-> OkLab conversion
 // =============================================================================
 
+/// OkLab coordinates of a color, ignoring its alpha
 pub fn rgb_to_oklab(col: Color32) -> (f32, f32, f32) {
+    srgba_to_oklab(col.into())
+}
+
+/// Opaque 8-bit color from OkLab, clamped to the sRGB gamut
+pub fn oklab_to_rgb(lab_l: f32, lab_a: f32, lab_b: f32) -> Color32 {
+    oklab_to_srgba(lab_l, lab_a, lab_b).into()
+}
+
+/// OkLab coordinates of a color, ignoring its alpha
+pub fn srgba_to_oklab(col: Srgba) -> (f32, f32, f32) {
     // Convert sRGB to linear RGB
-    let r = srgb_to_linear(col.r() as f32 / 255.0);
-    let g = srgb_to_linear(col.g() as f32 / 255.0);
-    let b = srgb_to_linear(col.b() as f32 / 255.0);
+    let [r, g, b] = col.to_linear();
 
     // Convert linear RGB to LMS cone space
     let l = 0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b;
@@ -222,7 +376,9 @@ pub fn rgb_to_oklab(col: Color32) -> (f32, f32, f32) {
     (lab_l, lab_a, lab_b)
 }
 
-pub fn oklab_to_rgb(lab_l: f32, lab_a: f32, lab_b: f32) -> Color32 {
+/// Opaque color from OkLab in extended sRGB: colors outside the sRGB gamut
+/// get channels outside [0, 1], which quantizing clips
+pub fn oklab_to_srgba(lab_l: f32, lab_a: f32, lab_b: f32) -> Srgba {
     // Convert Lab to LMS
     let l_ = lab_l + 0.3963377774 * lab_a + 0.2158037573 * lab_b;
     let m_ = lab_l - 0.1055613458 * lab_a - 0.0638541728 * lab_b;
@@ -238,16 +394,12 @@ pub fn oklab_to_rgb(lab_l: f32, lab_a: f32, lab_b: f32) -> Color32 {
     let g_linear = -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s;
     let b_linear = -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s;
 
-    // Convert linear RGB to sRGB and clamp
-    let r_srgb = linear_to_srgb(r_linear.clamp(0.0, 1.0));
-    let g_srgb = linear_to_srgb(g_linear.clamp(0.0, 1.0));
-    let b_srgb = linear_to_srgb(b_linear.clamp(0.0, 1.0));
+    // Convert linear RGB to sRGB
+    let r_srgb = linear_to_srgb(r_linear);
+    let g_srgb = linear_to_srgb(g_linear);
+    let b_srgb = linear_to_srgb(b_linear);
 
-    Color32::from_rgb(
-        (r_srgb * 255.0) as u8,
-        (g_srgb * 255.0) as u8,
-        (b_srgb * 255.0) as u8,
-    )
+    Srgba::new(r_srgb, g_srgb, b_srgb, 1.0)
 }
 
 // =============================================================================
@@ -272,14 +424,92 @@ fn oklch_to_oklab(l: f32, c: f32, h: f32) -> (f32, f32, f32) {
 
 /// Convert RGB directly to OkLCh
 pub fn rgb_to_oklch(col: Color32) -> (f32, f32, f32) {
-    let (l, a, b) = rgb_to_oklab(col);
-    oklab_to_oklch(l, a, b)
+    srgba_to_oklch(col.into())
 }
 
 /// Convert OkLCh directly to RGB
 pub fn oklch_to_rgb(l: f32, c: f32, h: f32) -> Color32 {
+    oklch_to_srgba(l, c, h).into()
+}
+
+pub fn srgba_to_oklch(col: Srgba) -> (f32, f32, f32) {
+    let (l, a, b) = srgba_to_oklab(col);
+    oklab_to_oklch(l, a, b)
+}
+
+pub fn oklch_to_srgba(l: f32, c: f32, h: f32) -> Srgba {
     let (lab_l, lab_a, lab_b) = oklch_to_oklab(l, c, h);
-    oklab_to_rgb(lab_l, lab_a, lab_b)
+    oklab_to_srgba(lab_l, lab_a, lab_b)
+}
+
+// =============================================================================
+// Hex notation
+// =============================================================================
+
+/// Parse `#RRGGBB`, `#RRGGBBAA` or `#RGB` (the leading `#` is optional)
+pub fn parse_hex_color(text: &str) -> Option<Color32> {
+    let hex = text.trim().trim_start_matches('#');
+    if !hex.is_ascii() {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        3 => {
+            let nibble = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|v| v * 17);
+            Some(Color32::from_rgb(nibble(0)?, nibble(1)?, nibble(2)?))
+        }
+        6 => Some(Color32::from_rgb(byte(0)?, byte(2)?, byte(4)?)),
+        8 => Some(Color32::from_rgba_unmultiplied(byte(0)?, byte(2)?, byte(4)?, byte(6)?)),
+        _ => None,
+    }
+}
+
+// =============================================================================
+// Color difference
+// =============================================================================
+
+/// Perceptual distance between two colors (Euclidean distance in OkLab).
+/// A difference of roughly 0.02 is the smallest step most people notice.
+pub fn delta_e(c1: Color32, c2: Color32) -> f32 {
+    let (l1, a1, b1) = rgb_to_oklab(c1);
+    let (l2, a2, b2) = rgb_to_oklab(c2);
+    ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+}
+
+/// Coordinates of a color in the given space: normalized [0, 1] channels for
+/// RGB, (L, a, b) for OkLab and (L, C, h in degrees) for OkLCh.
+/// Useful for precomputing colors that are compared many times.
+pub fn color_coordinates(col: Color32, space: ColorSpace) -> [f32; 3] {
+    match space {
+        ColorSpace::Rgb => {
+            let [r, g, b, _] = col.to_srgba_unmultiplied();
+            [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0]
+        }
+        ColorSpace::OkLab => {
+            let (l, a, b) = rgb_to_oklab(col);
+            [l, a, b]
+        }
+        ColorSpace::OkLCh => {
+            let (l, c, h) = rgb_to_oklch(col);
+            [l, c, h]
+        }
+    }
+}
+
+/// Distance between two sets of coordinates from `color_coordinates`
+pub fn coordinate_distance(p: [f32; 3], q: [f32; 3], space: ColorSpace) -> f32 {
+    match space {
+        ColorSpace::Rgb | ColorSpace::OkLab => {
+            ((p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) + (p[2] - q[2]).powi(2)).sqrt()
+        }
+        ColorSpace::OkLCh => {
+            // Hue difference is measured as a chord on the chroma circle, so
+            // hue matters less for greyish colors and wraps around at 360°
+            let dh = (p[2] - q[2]).to_radians();
+            let hue_term = 2.0 * (p[1] * q[1]).sqrt() * (dh / 2.0).sin();
+            ((p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) + hue_term.powi(2)).sqrt()
+        }
+    }
 }
 
 // =============================================================================
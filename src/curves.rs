@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 /// A curve maps a normalized input `t` in [0.0, 1.0] to an output value.
 /// This is the core abstraction for all interpolation in the palette system.
 /// 
@@ -9,7 +11,7 @@ pub trait Curve {
 
 /// The simplest curve: output equals input (optionally scaled by a factor).
 /// With factor=1.0, this is pure linear interpolation.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Linear {
     pub factor: f32,
 }
@@ -28,7 +30,7 @@ impl Curve for Linear {
 
 /// Ease-in curve: starts slow, accelerates toward the end.
 /// Higher exponent = more dramatic easing.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct EaseIn {
     pub exponent: f32,
 }
@@ -47,7 +49,7 @@ impl Curve for EaseIn {
 
 /// Ease-out curve: starts fast, decelerates toward the end.
 /// This is mathematically the "reflection" of EaseIn.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct EaseOut {
     pub exponent: f32,
 }
@@ -67,7 +69,7 @@ impl Curve for EaseOut {
 
 /// Ease-in-out curve: slow at both ends, fast in the middle.
 /// Creates a smooth S-curve transition.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct EaseInOut {
     pub exponent: f32,
 }
@@ -92,7 +94,7 @@ impl Curve for EaseInOut {
 
 /// Cubic Bezier curve defined by 4 control points.
 /// p0 and p3 are typically 0.0 and 1.0 for a standard 0→1 curve.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Bezier {
     pub p0: f32,
     pub p1: f32,
@@ -135,7 +137,7 @@ impl Curve for Bezier {
 
 /// All available curve types, selectable at runtime.
 /// Each variant stores its own parameters.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum CurveType {
     Linear(Linear),
     EaseIn(EaseIn),
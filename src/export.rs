@@ -0,0 +1,994 @@
+use std::collections::HashSet;
+
+use egui_macroquad::egui::Color32;
+
+use crate::app::App;
+use crate::color::{ColorSpace, Rounding, Srgba};
+use crate::gamut::{from_output, OutputSpace};
+use crate::gradient::{gradient_stops, GradientStop, DEFAULT_GRADIENT_TOLERANCE};
+use crate::palette::{deduplicate_colors, Palette};
+
+// =============================================================================
+// Palette export
+// =============================================================================
+//
+// Exports read from an `ExportSource` (the palette model plus its generated
+// colors, unquantized) and produce the text of a file. Colors are rounded to
+// 8 or 16 bits only as they are written. Channel values are in the palette's
+// output space, and code and data formats name that space in their header.
+// Hex is always sRGB: flat palette files and hex fields convert back to it,
+// while CSS-based formats switch to `color()` notation for other spaces.
+// Gradient stops are gamut mapped like every other color. There are two families of formats:
+// - Flat palette files: an ordered list of colors, optionally de-duplicated
+// - Token files: named colors (`primary-500`) grouped by swatch, plus aliases
+// - Source code: the same tokens as constants (`PRIMARY_500`) or tables
+// - Gradients: the continuous gradient of the selected swatch
+
+/// Name written into formats that store one
+const PALETTE_NAME: &str = "Chrma Palette";
+
+/// Output file formats
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// One `rrggbb` per line, as used by Lospec
+    #[default]
+    Hex,
+    /// GIMP / Inkscape / Krita palette
+    Gpl,
+    /// JASC palette (Paint Shop Pro, Aseprite, Pyxel Edit)
+    JascPal,
+    /// Paint.NET palette
+    PaintNet,
+    /// CSS custom properties on `:root`
+    CssVariables,
+    /// SCSS variables
+    Scss,
+    /// `theme.extend.colors` section of a Tailwind config, as JSON
+    TailwindJson,
+    /// W3C Design Tokens Community Group format
+    DesignTokens,
+    /// Rust `const` arrays of `[u8; 3]` (`[u8; 4]` once any color is translucent)
+    RustConst,
+    /// C/C++ header with `uint8_t` arrays
+    CHeader,
+    /// GLSL `vec3` constants (`vec4` with alpha)
+    Glsl,
+    /// HLSL `float3` constants (`float4` with alpha)
+    Hlsl,
+    /// Swatches as arrays of steps, for scripts and game data
+    Json,
+    /// Lua table
+    Lua,
+    /// CSS `linear-gradient()` of the selected swatch
+    CssGradient,
+    /// SVG `<linearGradient>` of the selected swatch
+    SvgGradient,
+    /// GIMP gradient of the selected swatch
+    GimpGradient,
+}
+
+impl ExportFormat {
+    pub const ALL: &'static [ExportFormat] = &[
+        ExportFormat::Hex,
+        ExportFormat::Gpl,
+        ExportFormat::JascPal,
+        ExportFormat::PaintNet,
+        ExportFormat::CssVariables,
+        ExportFormat::Scss,
+        ExportFormat::TailwindJson,
+        ExportFormat::DesignTokens,
+        ExportFormat::RustConst,
+        ExportFormat::CHeader,
+        ExportFormat::Glsl,
+        ExportFormat::Hlsl,
+        ExportFormat::Json,
+        ExportFormat::Lua,
+        ExportFormat::CssGradient,
+        ExportFormat::SvgGradient,
+        ExportFormat::GimpGradient,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Hex => "Hex list (.hex)",
+            ExportFormat::Gpl => "GIMP palette (.gpl)",
+            ExportFormat::JascPal => "JASC palette (.pal)",
+            ExportFormat::PaintNet => "Paint.NET palette (.txt)",
+            ExportFormat::CssVariables => "CSS custom properties (.css)",
+            ExportFormat::Scss => "SCSS variables (.scss)",
+            ExportFormat::TailwindJson => "Tailwind colors (.json)",
+            ExportFormat::DesignTokens => "W3C Design Tokens (.tokens.json)",
+            ExportFormat::RustConst => "Rust constants (.rs)",
+            ExportFormat::CHeader => "C/C++ header (.h)",
+            ExportFormat::Glsl => "GLSL constants (.glsl)",
+            ExportFormat::Hlsl => "HLSL constants (.hlsl)",
+            ExportFormat::Json => "JSON (.json)",
+            ExportFormat::Lua => "Lua table (.lua)",
+            ExportFormat::CssGradient => "CSS gradient (.css)",
+            ExportFormat::SvgGradient => "SVG gradient (.svg)",
+            ExportFormat::GimpGradient => "GIMP gradient (.ggr)",
+        }
+    }
+
+    /// File extension, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Hex => "hex",
+            ExportFormat::Gpl => "gpl",
+            ExportFormat::JascPal => "pal",
+            ExportFormat::PaintNet => "txt",
+            ExportFormat::CssVariables => "css",
+            ExportFormat::Scss => "scss",
+            ExportFormat::TailwindJson => "json",
+            ExportFormat::DesignTokens => "tokens.json",
+            ExportFormat::RustConst => "rs",
+            ExportFormat::CHeader => "h",
+            ExportFormat::Glsl => "glsl",
+            ExportFormat::Hlsl => "hlsl",
+            ExportFormat::Json => "json",
+            ExportFormat::Lua => "lua",
+            ExportFormat::CssGradient => "css",
+            ExportFormat::SvgGradient => "svg",
+            ExportFormat::GimpGradient => "ggr",
+        }
+    }
+
+    /// Whether this format is a flat color list (as opposed to named tokens)
+    pub fn is_flat(&self) -> bool {
+        matches!(
+            self,
+            ExportFormat::Hex | ExportFormat::Gpl | ExportFormat::JascPal | ExportFormat::PaintNet
+        )
+    }
+
+    /// Whether this format writes normalized float colors, which can be linear
+    pub fn is_shader(&self) -> bool {
+        matches!(self, ExportFormat::Glsl | ExportFormat::Hlsl)
+    }
+
+    /// Whether this format writes channel values at a choice of `ChannelDepth`
+    pub fn has_channel_depth(&self) -> bool {
+        matches!(
+            self,
+            ExportFormat::RustConst | ExportFormat::CHeader | ExportFormat::Json | ExportFormat::Lua
+        )
+    }
+
+    /// Whether this format writes the continuous gradient of one swatch
+    pub fn is_gradient(&self) -> bool {
+        matches!(
+            self,
+            ExportFormat::CssGradient | ExportFormat::SvgGradient | ExportFormat::GimpGradient
+        )
+    }
+}
+
+/// How code and data formats write each channel
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ChannelDepth {
+    /// Integers from 0 to 255
+    #[default]
+    Eight,
+    /// Integers from 0 to 65535
+    Sixteen,
+    /// Unquantized values from 0.0 to 1.0
+    Float,
+}
+
+impl ChannelDepth {
+    pub const ALL: &'static [ChannelDepth] = &[ChannelDepth::Eight, ChannelDepth::Sixteen, ChannelDepth::Float];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChannelDepth::Eight => "8-bit",
+            ChannelDepth::Sixteen => "16-bit",
+            ChannelDepth::Float => "Float",
+        }
+    }
+}
+
+/// Everything an export reads from
+pub struct ExportSource<'a> {
+    pub palette: &'a Palette,
+    /// Generated colors for each swatch, in palette order, in the output
+    /// space and before quantization
+    pub colors: Vec<Vec<Srgba>>,
+    /// Flat formats write the de-duplicated color list
+    pub merge_duplicates: bool,
+    /// Write linear-light values instead of sRGB: always possible in shader
+    /// formats, and in code and data formats at 16-bit or float depth
+    pub linear: bool,
+    /// How colors are quantized to 8 or 16 bits
+    pub rounding: Rounding,
+    /// Channel values of code and data formats
+    pub depth: ChannelDepth,
+    /// Swatch written by gradient formats
+    pub current_swatch: usize,
+    /// Largest ΔE gradient formats may stray from the swatch's curve
+    pub gradient_tolerance: f32,
+}
+
+impl<'a> ExportSource<'a> {
+    /// `colors` are the app's `output_colors`, which callers may cache
+    pub fn from_app(app: &'a App, colors: Vec<Vec<Srgba>>, merge_duplicates: bool) -> Self {
+        Self {
+            palette: &app.palette,
+            colors,
+            merge_duplicates,
+            linear: false,
+            rounding: Rounding::default(),
+            depth: ChannelDepth::default(),
+            current_swatch: app.current_swatch_index,
+            gradient_tolerance: DEFAULT_GRADIENT_TOLERANCE,
+        }
+    }
+
+    /// The colors written by flat formats. Hex lists and palette files are
+    /// read as sRGB, so output colors are converted back and clipped to it.
+    pub fn flat_colors(&self) -> Vec<Color32> {
+        let space = self.palette.output_space;
+        let colors: Vec<Color32> = self
+            .colors
+            .iter()
+            .flatten()
+            .map(|&c| from_output(c, space).quantize(self.rounding))
+            .collect();
+        if self.merge_duplicates {
+            deduplicate_colors(&colors, self.palette.merge_threshold)
+        } else {
+            colors
+        }
+    }
+}
+
+/// Render the palette in the given format
+pub fn export_palette(source: &ExportSource, format: ExportFormat) -> String {
+    let values = Values::from_source(source);
+    match format {
+        ExportFormat::Hex | ExportFormat::Gpl | ExportFormat::JascPal | ExportFormat::PaintNet => {
+            export_colors(&source.flat_colors(), format)
+        }
+        ExportFormat::CssVariables => export_css(&collect_tokens(source), values),
+        ExportFormat::Scss => export_scss(&collect_tokens(source), values),
+        ExportFormat::TailwindJson => export_tailwind(&collect_tokens(source), values),
+        ExportFormat::DesignTokens => export_design_tokens(&collect_tokens(source), values),
+        ExportFormat::RustConst => export_rust(&collect_tokens(source), values),
+        ExportFormat::CHeader => export_c_header(&collect_tokens(source), values),
+        ExportFormat::Glsl | ExportFormat::Hlsl => export_shader(&collect_tokens(source), format, values),
+        ExportFormat::Json => export_json(&collect_tokens(source), values),
+        ExportFormat::Lua => export_lua(&collect_tokens(source), values),
+        ExportFormat::CssGradient | ExportFormat::SvgGradient | ExportFormat::GimpGradient => {
+            export_gradient(source, format)
+        }
+    }
+}
+
+/// CSS `color()` notation for an output color in `space`
+fn css_color(c: Srgba, space: OutputSpace) -> String {
+    let alpha = if c.is_opaque() { String::new() } else { format!(" / {:.3}", c.a) };
+    format!("color({} {:.5} {:.5} {:.5}{alpha})", space.css_name(), c.r, c.g, c.b)
+}
+
+/// `#rrggbb`, or `#rrggbbaa` for translucent colors
+pub fn hex(c: Color32) -> String {
+    let [r, g, b, a] = c.to_srgba_unmultiplied();
+    if c.is_opaque() {
+        format!("#{r:02x}{g:02x}{b:02x}")
+    } else {
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+}
+
+/// How token colors are written: hex notation is always 8-bit, while code
+/// and data formats write channels at the chosen depth
+#[derive(Clone, Copy)]
+struct Values {
+    rounding: Rounding,
+    depth: ChannelDepth,
+    linear: bool,
+    space: OutputSpace,
+    /// Some color is translucent, so code and data formats write RGBA
+    alpha: bool,
+}
+
+impl Values {
+    fn from_source(source: &ExportSource) -> Self {
+        let translucent = source.colors.iter().flatten().any(|c| !c.is_opaque())
+            || source.palette.named_colors.iter().any(|c| !c.color.is_opaque());
+        Self {
+            rounding: source.rounding,
+            depth: source.depth,
+            linear: source.linear,
+            space: source.palette.output_space,
+            alpha: translucent,
+        }
+    }
+
+    /// 3 for RGB, 4 for RGBA
+    fn channel_count(&self) -> usize {
+        if self.alpha {
+            4
+        } else {
+            3
+        }
+    }
+
+    /// Hex is always read as sRGB, so output colors are converted back to it
+    fn hex(&self, c: Srgba) -> String {
+        hex(from_output(c, self.space).quantize(self.rounding))
+    }
+
+    /// Hex in sRGB, since CSS reads hex as sRGB; `color()` in wider spaces
+    fn css(&self, c: Srgba) -> String {
+        if self.space == OutputSpace::Srgb {
+            return self.hex(c);
+        }
+        css_color(c, self.space)
+    }
+
+    /// The space and transfer of channel values, for labeling the output
+    fn encoding(&self, linear: bool) -> String {
+        if linear {
+            format!("{}, linear", self.space.name())
+        } else {
+            self.space.name().to_string()
+        }
+    }
+
+    /// Code and data formats only write linear light at 16-bit and float depth
+    fn linear_channels(&self) -> bool {
+        self.linear && self.depth != ChannelDepth::Eight
+    }
+
+    fn decode(&self, c: Srgba) -> [f32; 3] {
+        [c.r, c.g, c.b].map(|x| self.space.decode(x))
+    }
+
+    /// Red, green and blue (and alpha, if any color is translucent) at the
+    /// channel depth. Linear light is only written at 16-bit and float depth,
+    /// where it doesn't band; alpha is never linearized.
+    fn channels(&self, c: Srgba) -> Vec<String> {
+        let [r, g, b] = if self.linear_channels() {
+            self.decode(c)
+        } else {
+            [c.r, c.g, c.b]
+        };
+        [r, g, b, c.a][..self.channel_count()]
+            .iter()
+            .map(|&x| match self.depth {
+                ChannelDepth::Eight => self.rounding.apply(x, 255.0).to_string(),
+                ChannelDepth::Sixteen => self.rounding.apply(x, 65535.0).to_string(),
+                ChannelDepth::Float => format!("{x:.6}"),
+            })
+            .collect()
+    }
+
+    /// Shaders always get unquantized floats
+    fn floats(&self, c: Srgba) -> Vec<f32> {
+        let [r, g, b] = if self.linear { self.decode(c) } else { [c.r, c.g, c.b] };
+        [r, g, b, c.a][..self.channel_count()].to_vec()
+    }
+
+    fn rust_type(&self) -> &'static str {
+        match self.depth {
+            ChannelDepth::Eight => "u8",
+            ChannelDepth::Sixteen => "u16",
+            ChannelDepth::Float => "f32",
+        }
+    }
+
+    fn c_type(&self) -> &'static str {
+        match self.depth {
+            ChannelDepth::Eight => "uint8_t",
+            ChannelDepth::Sixteen => "uint16_t",
+            ChannelDepth::Float => "float",
+        }
+    }
+}
+
+// =============================================================================
+// Flat palette files
+// =============================================================================
+
+fn export_colors(colors: &[Color32], format: ExportFormat) -> String {
+    // Channels are written straight, not premultiplied by alpha. Only
+    // paint.net files have an alpha channel.
+    let mut out = String::new();
+    match format {
+        ExportFormat::Hex => {
+            for c in colors {
+                let [r, g, b, _] = c.to_srgba_unmultiplied();
+                out += &format!("{r:02x}{g:02x}{b:02x}\n");
+            }
+        }
+        ExportFormat::Gpl => {
+            out += &format!("GIMP Palette\nName: {PALETTE_NAME}\nColumns: 0\n#\n");
+            for c in colors {
+                let [r, g, b, _] = c.to_srgba_unmultiplied();
+                out += &format!("{r:3} {g:3} {b:3}\t{}\n", hex(*c));
+            }
+        }
+        ExportFormat::JascPal => {
+            out += &format!("JASC-PAL\n0100\n{}\n", colors.len());
+            for c in colors {
+                let [r, g, b, _] = c.to_srgba_unmultiplied();
+                out += &format!("{r} {g} {b}\n");
+            }
+        }
+        ExportFormat::PaintNet => {
+            out += &format!(";paint.net Palette File\n;{PALETTE_NAME}\n;Colors: {}\n", colors.len());
+            for c in colors {
+                let [r, g, b, a] = c.to_srgba_unmultiplied();
+                out += &format!("{a:02X}{r:02X}{g:02X}{b:02X}\n");
+            }
+        }
+        _ => unreachable!("not a flat format"),
+    }
+    out
+}
+
+// =============================================================================
+// Design tokens
+// =============================================================================
+
+/// One swatch as a group of named steps (`primary` → `500`)
+pub struct TokenGroup {
+    pub name: String,
+    pub steps: Vec<(String, Srgba)>,
+}
+
+/// A semantic name that refers to one step of a group
+pub struct TokenAlias {
+    pub name: String,
+    pub group: String,
+    pub step: String,
+    /// Position of the step within the group
+    pub index: usize,
+    pub color: Srgba,
+}
+
+/// The palette as named tokens. All names are slugs, safe in any format.
+pub struct Tokens {
+    pub groups: Vec<TokenGroup>,
+    pub aliases: Vec<TokenAlias>,
+    /// Palette-level named colors
+    pub named: Vec<(String, Srgba)>,
+}
+
+/// Lowercase, with runs of anything other than letters and digits replaced by `-`
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+pub fn collect_tokens(source: &ExportSource) -> Tokens {
+    let palette = source.palette;
+    let mut groups: Vec<TokenGroup> = Vec::new();
+    let mut aliases = Vec::new();
+
+    for (index, swatch) in palette.swatches.iter().enumerate() {
+        let colors = source.colors.get(index).map_or(&[][..], |c| &c[..]);
+
+        // Keep group names unique, even if two swatches share a name
+        let mut name = slugify(&swatch.display_name(index));
+        if name.is_empty() || groups.iter().any(|g| g.name == name) {
+            name = format!("{}-{}", if name.is_empty() { "swatch" } else { &name }, index + 1);
+        }
+
+        // Step names must be unique within the group and never empty; an
+        // empty slug falls back to the step's number
+        let mut taken = HashSet::new();
+        let step_names: Vec<String> = palette
+            .step_naming
+            .step_names(colors.len())
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                let mut slug = slugify(step);
+                if slug.is_empty() {
+                    slug = (i + 1).to_string();
+                }
+                let mut unique = slug.clone();
+                let mut suffix = 2;
+                while !taken.insert(unique.clone()) {
+                    unique = format!("{slug}-{suffix}");
+                    suffix += 1;
+                }
+                unique
+            })
+            .collect();
+
+        for alias in &swatch.step_aliases {
+            let alias_name = slugify(&alias.name);
+            if let (false, Some(&color)) = (alias_name.is_empty(), colors.get(alias.step)) {
+                aliases.push(TokenAlias {
+                    name: alias_name,
+                    group: name.clone(),
+                    step: step_names[alias.step].clone(),
+                    index: alias.step,
+                    color,
+                });
+            }
+        }
+
+        groups.push(TokenGroup {
+            name,
+            steps: step_names.into_iter().zip(colors.iter().copied()).collect(),
+        });
+    }
+
+    let named = palette
+        .named_colors
+        .iter()
+        .map(|c| (slugify(&c.name), palette.to_output(c.color)))
+        .filter(|(name, _)| !name.is_empty())
+        .collect();
+
+    Tokens { groups, aliases, named }
+}
+
+fn export_css(tokens: &Tokens, values: Values) -> String {
+    let mut out = String::from(":root {\n");
+    for group in &tokens.groups {
+        for (step, color) in &group.steps {
+            out += &format!("  --{}-{step}: {};\n", group.name, values.css(*color));
+        }
+    }
+    for (name, color) in &tokens.named {
+        out += &format!("  --{name}: {};\n", values.css(*color));
+    }
+    for alias in &tokens.aliases {
+        out += &format!("  --{}: var(--{}-{});\n", alias.name, alias.group, alias.step);
+    }
+    out += "}\n";
+    out
+}
+
+fn export_scss(tokens: &Tokens, values: Values) -> String {
+    let mut out = String::new();
+    for group in &tokens.groups {
+        for (step, color) in &group.steps {
+            out += &format!("${}-{step}: {};\n", group.name, values.css(*color));
+        }
+    }
+    for (name, color) in &tokens.named {
+        out += &format!("${name}: {};\n", values.css(*color));
+    }
+    for alias in &tokens.aliases {
+        out += &format!("${}: ${}-{};\n", alias.name, alias.group, alias.step);
+    }
+    out
+}
+
+/// Tailwind has no alias syntax, so aliases are written as plain colors
+fn export_tailwind(tokens: &Tokens, values: Values) -> String {
+    let mut entries: Vec<String> = Vec::new();
+    for group in &tokens.groups {
+        let steps: Vec<String> = group
+            .steps
+            .iter()
+            .map(|(step, color)| format!("          \"{step}\": \"{}\"", values.css(*color)))
+            .collect();
+        entries.push(format!("        \"{}\": {{\n{}\n        }}", group.name, steps.join(",\n")));
+    }
+    for (name, color) in &tokens.named {
+        entries.push(format!("        \"{name}\": \"{}\"", values.css(*color)));
+    }
+    for alias in &tokens.aliases {
+        entries.push(format!("        \"{}\": \"{}\"", alias.name, values.css(alias.color)));
+    }
+
+    format!(
+        "{{\n  \"theme\": {{\n    \"extend\": {{\n      \"colors\": {{\n{}\n      }}\n    }}\n  }}\n}}\n",
+        entries.join(",\n")
+    )
+}
+
+/// Aliases use the `{group.token}` reference syntax
+fn export_design_tokens(tokens: &Tokens, values: Values) -> String {
+    let token = |value: String| format!("{{ \"$type\": \"color\", \"$value\": \"{value}\" }}");
+
+    let mut entries: Vec<String> = Vec::new();
+    for group in &tokens.groups {
+        let steps: Vec<String> = group
+            .steps
+            .iter()
+            .map(|(step, color)| format!("    \"{step}\": {}", token(values.css(*color))))
+            .collect();
+        entries.push(format!("  \"{}\": {{\n{}\n  }}", group.name, steps.join(",\n")));
+    }
+    for (name, color) in &tokens.named {
+        entries.push(format!("  \"{name}\": {}", token(values.css(*color))));
+    }
+    for alias in &tokens.aliases {
+        let reference = format!("{{{}.{}}}", alias.group, alias.step);
+        entries.push(format!("  \"{}\": {}", alias.name, token(reference)));
+    }
+
+    format!("{{\n{}\n}}\n", entries.join(",\n"))
+}
+
+// =============================================================================
+// Source code
+// =============================================================================
+//
+// Identifiers are built from the token slugs: `primary` + `500` becomes
+// `PRIMARY_500`. Each swatch is written as one constant per step, followed by
+// an array of the steps in order. Empty swatches are skipped, since C and the
+// shading languages have no zero-length arrays.
+//
+// Different slugs can map to the same identifier (a named color `primary-1`
+// and step `1` of `primary` are both `PRIMARY_1`), so every symbol is claimed
+// once up front and later claims get a `_2`, `_3`, ... suffix.
+
+/// `SCREAMING_SNAKE_CASE` identifier from token slugs
+fn identifier(parts: &[&str]) -> String {
+    let mut ident = parts.join("_").replace('-', "_").to_ascii_uppercase();
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+fn code_groups(tokens: &Tokens) -> impl Iterator<Item = &TokenGroup> {
+    tokens.groups.iter().filter(|group| !group.steps.is_empty())
+}
+
+/// Unique identifiers for every symbol of a code export, in token order
+struct CodeNames {
+    /// Array and step identifiers of each non-empty group
+    groups: Vec<(String, Vec<String>)>,
+    named: Vec<String>,
+    /// Alias identifier and the step identifier it refers to
+    aliases: Vec<(String, String)>,
+}
+
+impl CodeNames {
+    /// `reserved` holds symbols the format defines itself, such as a header guard
+    fn new(tokens: &Tokens, reserved: &[&str]) -> Self {
+        let mut taken: HashSet<String> = reserved.iter().map(|name| name.to_string()).collect();
+        let mut claim = |ident: String| {
+            let mut unique = ident.clone();
+            let mut suffix = 2;
+            while !taken.insert(unique.clone()) {
+                unique = format!("{ident}_{suffix}");
+                suffix += 1;
+            }
+            unique
+        };
+
+        // Steps are claimed first, so they keep their plain names
+        let steps: Vec<Vec<String>> = code_groups(tokens)
+            .map(|group| group.steps.iter().map(|(step, _)| claim(identifier(&[&group.name, step]))).collect())
+            .collect();
+        let groups: Vec<(String, Vec<String>)> = code_groups(tokens)
+            .zip(steps)
+            .map(|(group, steps)| (claim(identifier(&[&group.name])), steps))
+            .collect();
+        let named = tokens.named.iter().map(|(name, _)| claim(identifier(&[name]))).collect();
+        let aliases = tokens
+            .aliases
+            .iter()
+            .filter_map(|alias| {
+                let group = code_groups(tokens).position(|group| group.name == alias.group)?;
+                let target = groups[group].1.get(alias.index)?.clone();
+                Some((claim(identifier(&[&alias.name])), target))
+            })
+            .collect();
+
+        Self { groups, named, aliases }
+    }
+}
+
+fn export_rust(tokens: &Tokens, values: Values) -> String {
+    let rgb = |c: Srgba| format!("[{}]", values.channels(c).join(", "));
+    let ty = values.rust_type();
+    let n = values.channel_count();
+
+    let names = CodeNames::new(tokens, &[]);
+
+    let encoding = values.encoding(values.linear_channels());
+    let mut out = format!("// {PALETTE_NAME} ({encoding})\n");
+    for (group, (array, steps)) in code_groups(tokens).zip(&names.groups) {
+        out += "\n";
+        for ((_, color), name) in group.steps.iter().zip(steps) {
+            out += &format!("pub const {name}: [{ty}; {n}] = {};\n", rgb(*color));
+        }
+        out += &format!(
+            "pub const {array}: [[{ty}; {n}]; {}] = [{}];\n",
+            steps.len(),
+            steps.join(", ")
+        );
+    }
+    if !names.named.is_empty() || !names.aliases.is_empty() {
+        out += "\n";
+    }
+    for ((_, color), name) in tokens.named.iter().zip(&names.named) {
+        out += &format!("pub const {name}: [{ty}; {n}] = {};\n", rgb(*color));
+    }
+    for (name, target) in &names.aliases {
+        out += &format!("pub const {name}: [{ty}; {n}] = {target};\n");
+    }
+    out
+}
+
+fn export_c_header(tokens: &Tokens, values: Values) -> String {
+    let rgb = |c: Srgba| format!("{{ {} }}", values.channels(c).join(", "));
+    let ty = values.c_type();
+    let n = values.channel_count();
+    let guard = format!("{}_H", identifier(&[&slugify(PALETTE_NAME)]));
+    let names = CodeNames::new(tokens, &[&guard]);
+
+    let encoding = values.encoding(values.linear_channels());
+    let mut out = format!("/* {PALETTE_NAME} ({encoding}) */\n#ifndef {guard}\n#define {guard}\n\n#include <stdint.h>\n");
+    for (group, (array, steps)) in code_groups(tokens).zip(&names.groups) {
+        out += "\n";
+        for ((_, color), name) in group.steps.iter().zip(steps) {
+            out += &format!("static const {ty} {name}[{n}] = {};\n", rgb(*color));
+        }
+        out += &format!("static const {ty} {array}[{}][{n}] = {{\n", group.steps.len());
+        for (step, color) in &group.steps {
+            out += &format!("    {}, /* {step} */\n", rgb(*color));
+        }
+        out += "};\n";
+    }
+    if !names.named.is_empty() || !names.aliases.is_empty() {
+        out += "\n";
+    }
+    for ((_, color), name) in tokens.named.iter().zip(&names.named) {
+        out += &format!("static const {ty} {name}[{n}] = {};\n", rgb(*color));
+    }
+    for (name, target) in &names.aliases {
+        out += &format!("#define {name} {target}\n");
+    }
+    out += &format!("\n#endif /* {guard} */\n");
+    out
+}
+
+/// GLSL and HLSL differ only in type names and array syntax
+fn export_shader(tokens: &Tokens, format: ExportFormat, values: Values) -> String {
+    let hlsl = format == ExportFormat::Hlsl;
+    let n = values.channel_count();
+    let ty = if hlsl { format!("float{n}") } else { format!("vec{n}") };
+    let decl = if hlsl { format!("static const {ty}") } else { format!("const {ty}") };
+    let value = |c: Srgba| {
+        let floats: Vec<String> = values.floats(c).iter().map(|x| format!("{x:.6}")).collect();
+        format!("{ty}({})", floats.join(", "))
+    };
+
+    let encoding = values.encoding(values.linear);
+    let names = CodeNames::new(tokens, &[]);
+    let mut out = format!("// {PALETTE_NAME} ({encoding})\n");
+    for (group, (array, steps)) in code_groups(tokens).zip(&names.groups) {
+        out += "\n";
+        for ((_, color), name) in group.steps.iter().zip(steps) {
+            out += &format!("{decl} {name} = {};\n", value(*color));
+        }
+        let count = steps.len();
+        out += &if hlsl {
+            format!("{decl} {array}[{count}] = {{ {} }};\n", steps.join(", "))
+        } else {
+            format!("{decl} {array}[{count}] = {ty}[{count}]({});\n", steps.join(", "))
+        };
+    }
+    if !names.named.is_empty() || !names.aliases.is_empty() {
+        out += "\n";
+    }
+    for ((_, color), name) in tokens.named.iter().zip(&names.named) {
+        out += &format!("{decl} {name} = {};\n", value(*color));
+    }
+    for (name, target) in &names.aliases {
+        out += &format!("{decl} {name} = {target};\n");
+    }
+    out
+}
+
+/// Swatches keep their step order, so they are arrays rather than objects
+fn export_json(tokens: &Tokens, values: Values) -> String {
+    let key = if values.alpha { "rgba" } else { "rgb" };
+    let color = |c: Srgba| format!("\"hex\": \"{}\", \"{key}\": [{}]", values.hex(c), values.channels(c).join(", "));
+
+    let groups: Vec<String> = tokens
+        .groups
+        .iter()
+        .map(|group| {
+            let steps: Vec<String> = group
+                .steps
+                .iter()
+                .map(|(step, c)| format!("      {{ \"name\": \"{step}\", {} }}", color(*c)))
+                .collect();
+            format!("    \"{}\": [\n{}\n    ]", group.name, steps.join(",\n"))
+        })
+        .collect();
+    let named: Vec<String> = tokens
+        .named
+        .iter()
+        .map(|(name, c)| format!("    \"{name}\": {{ {} }}", color(*c)))
+        .collect();
+    let aliases: Vec<String> = tokens
+        .aliases
+        .iter()
+        .map(|alias| {
+            format!(
+                "    \"{}\": {{ \"swatch\": \"{}\", \"step\": \"{}\", {} }}",
+                alias.name,
+                alias.group,
+                alias.step,
+                color(alias.color)
+            )
+        })
+        .collect();
+
+    let section = |entries: &[String]| {
+        if entries.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{\n{}\n  }}", entries.join(",\n"))
+        }
+    };
+    format!(
+        "{{\n  \"name\": \"{PALETTE_NAME}\",\n  \"space\": \"{}\",\n  \"swatches\": {},\n  \"named\": {},\n  \"aliases\": {}\n}}\n",
+        values.encoding(values.linear_channels()),
+        section(&groups),
+        section(&named),
+        section(&aliases)
+    )
+}
+
+/// Swatches are sequences (1-based, like any Lua array) with a `name` per step
+fn export_lua(tokens: &Tokens, values: Values) -> String {
+    let rgb = |c: Srgba| values.channels(c).join(", ");
+
+    let encoding = values.encoding(values.linear_channels());
+    let mut out = format!("-- {PALETTE_NAME} ({encoding})\nlocal palette = {{\n");
+    for group in &tokens.groups {
+        out += &format!("  [\"{}\"] = {{\n", group.name);
+        for (step, color) in &group.steps {
+            out += &format!("    {{ {}, name = \"{step}\" }},\n", rgb(*color));
+        }
+        out += "  },\n";
+    }
+    for (name, color) in &tokens.named {
+        out += &format!("  [\"{name}\"] = {{ {} }},\n", rgb(*color));
+    }
+    out += "}\n";
+    for alias in &tokens.aliases {
+        out += &format!(
+            "palette[\"{}\"] = palette[\"{}\"][{}]\n",
+            alias.name,
+            alias.group,
+            alias.index + 1
+        );
+    }
+    out += "\nreturn palette\n";
+    out
+}
+
+// =============================================================================
+// Continuous gradients
+// =============================================================================
+
+/// Size of the preview rectangle in SVG files
+const SVG_WIDTH: u32 = 256;
+const SVG_HEIGHT: u32 = 32;
+
+/// The color space a gradient format interpolates in. Only CSS can be told
+/// to use OkLab/OkLCh; SVG and GIMP always blend sRGB values.
+fn gradient_space(format: ExportFormat, swatch_space: ColorSpace) -> ColorSpace {
+    if format == ExportFormat::CssGradient {
+        swatch_space
+    } else {
+        ColorSpace::Rgb
+    }
+}
+
+/// The stops of the selected swatch, and its name as a slug. Stop colors are
+/// gamut mapped like the generated colors, then quantized in sRGB.
+pub fn gradient_export_stops(source: &ExportSource, format: ExportFormat) -> Option<(String, Vec<GradientStop>)> {
+    let palette = source.palette;
+    let swatch = palette.swatches.get(source.current_swatch)?;
+    let space = gradient_space(format, swatch.color_space);
+    let mut name = slugify(&swatch.display_name(source.current_swatch));
+    if name.is_empty() {
+        name = format!("swatch-{}", source.current_swatch + 1);
+    }
+    let stops = gradient_stops(swatch, space, source.gradient_tolerance)
+        .into_iter()
+        .map(|stop| GradientStop {
+            color: from_output(stop_output(source, stop), palette.output_space).quantize(source.rounding),
+            ..stop
+        })
+        .collect();
+    Some((name, stops))
+}
+
+/// A stop's color in the output space
+fn stop_output(source: &ExportSource, stop: GradientStop) -> Srgba {
+    let swatch = &source.palette.swatches[source.current_swatch];
+    source.palette.to_output(swatch.sample_precise(stop.position))
+}
+
+fn export_gradient(source: &ExportSource, format: ExportFormat) -> String {
+    let Some((name, stops)) = gradient_export_stops(source, format) else {
+        return String::new();
+    };
+    match format {
+        ExportFormat::CssGradient => export_css_gradient(source, &name, &stops),
+        ExportFormat::SvgGradient => export_svg_gradient(&name, &stops),
+        ExportFormat::GimpGradient => export_ggr(&name, &stops),
+        _ => unreachable!("not a gradient format"),
+    }
+}
+
+/// Like CSS variables, stops beyond sRGB are written with `color()`
+fn export_css_gradient(source: &ExportSource, name: &str, stops: &[GradientStop]) -> String {
+    let space = source.palette.output_space;
+    let method = match source.palette.swatches[source.current_swatch].color_space {
+        ColorSpace::Rgb => "",
+        ColorSpace::OkLab => " in oklab",
+        ColorSpace::OkLCh => " in oklch",
+    };
+    let stops: Vec<String> = stops
+        .iter()
+        .map(|&stop| {
+            let color = if space == OutputSpace::Srgb {
+                hex(stop.color)
+            } else {
+                css_color(stop_output(source, stop), space)
+            };
+            format!("    {color} {:.2}%", stop.position * 100.0)
+        })
+        .collect();
+    format!(
+        ":root {{\n  --{name}-gradient: linear-gradient(\n    to right{method},\n{}\n  );\n}}\n",
+        stops.join(",\n")
+    )
+}
+
+fn export_svg_gradient(name: &str, stops: &[GradientStop]) -> String {
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{SVG_WIDTH}\" height=\"{SVG_HEIGHT}\" viewBox=\"0 0 {SVG_WIDTH} {SVG_HEIGHT}\">\n"
+    );
+    out += &format!("  <defs>\n    <linearGradient id=\"{name}\" x1=\"0\" y1=\"0\" x2=\"1\" y2=\"0\">\n");
+    for stop in stops {
+        // SVG 1.1 reads only `#rrggbb`, so alpha goes in stop-opacity
+        let [r, g, b, a] = stop.color.to_srgba_unmultiplied();
+        let opacity = if a == 255 { String::new() } else { format!(" stop-opacity=\"{:.3}\"", a as f32 / 255.0) };
+        out += &format!(
+            "      <stop offset=\"{:.4}\" stop-color=\"#{r:02x}{g:02x}{b:02x}\"{opacity}/>\n",
+            stop.position
+        );
+    }
+    out += "    </linearGradient>\n  </defs>\n";
+    out += &format!("  <rect width=\"{SVG_WIDTH}\" height=\"{SVG_HEIGHT}\" fill=\"url(#{name})\"/>\n</svg>\n");
+    out
+}
+
+/// One linear RGB segment per pair of stops, with the midpoint centered
+fn export_ggr(name: &str, stops: &[GradientStop]) -> String {
+    let rgba = |c: Color32| {
+        let [r, g, b, a] = c.to_srgba_unmultiplied().map(|x| x as f32 / 255.0);
+        format!("{r:.6} {g:.6} {b:.6} {a:.6}")
+    };
+
+    let mut out = format!("GIMP Gradient\nName: {name}\n{}\n", stops.len().saturating_sub(1));
+    for pair in stops.windows(2) {
+        let (left, right) = (pair[0], pair[1]);
+        out += &format!(
+            "{:.6} {:.6} {:.6} {} {} 0 0 0 0\n",
+            left.position,
+            (left.position + right.position) / 2.0,
+            right.position,
+            rgba(left.color),
+            rgba(right.color)
+        );
+    }
+    out
+}
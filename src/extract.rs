@@ -0,0 +1,404 @@
+use egui_macroquad::egui::Color32;
+
+use crate::color::{oklab_to_rgb, rgb_to_oklab, rgb_to_oklch, ColorSpace};
+use crate::fit::{fit_swatch, DEFAULT_FIT_TOLERANCE};
+use crate::palette::{Swatch, MIN_SWATCH_SIZE};
+
+// =============================================================================
+// Palette extraction from reference images
+// =============================================================================
+//
+// Extraction happens in three stages:
+// 1. Load the image and subsample its pixels (clustering every pixel of a
+//    large painting is slow and doesn't change the result much)
+// 2. Cluster the pixels into a small number of dominant colors
+// 3. Group the dominant colors by hue into ramps, and fit a swatch to each
+
+/// Colors with less OkLCh chroma than this are grouped into the neutral ramp
+const NEUTRAL_CHROMA: f32 = 0.03;
+/// Number of refinement passes for k-means
+const KMEANS_ITERATIONS: usize = 16;
+/// Deepest octree level (one bit per channel per level)
+const OCTREE_DEPTH: usize = 6;
+
+// =============================================================================
+// Clustering
+// =============================================================================
+
+/// Algorithm used to find the dominant colors of an image
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ClusterMethod {
+    #[default]
+    KMeans,
+    MedianCut,
+    Octree,
+}
+
+impl ClusterMethod {
+    pub const ALL: &'static [ClusterMethod] = &[
+        ClusterMethod::KMeans,
+        ClusterMethod::MedianCut,
+        ClusterMethod::Octree,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClusterMethod::KMeans => "k-means (OkLab)",
+            ClusterMethod::MedianCut => "Median cut",
+            ClusterMethod::Octree => "Octree",
+        }
+    }
+}
+
+/// A dominant color and the fraction of sampled pixels it represents
+#[derive(Clone, Copy, Debug)]
+pub struct Cluster {
+    pub color: Color32,
+    /// Fraction of pixels in [0.0, 1.0]
+    pub weight: f32,
+}
+
+/// Reduce `pixels` to at most `count` dominant colors, sorted by weight (largest first)
+pub fn cluster_colors(pixels: &[Color32], count: usize, method: ClusterMethod) -> Vec<Cluster> {
+    if pixels.is_empty() || count == 0 {
+        return Vec::new();
+    }
+
+    let mut clusters = match method {
+        ClusterMethod::KMeans => kmeans(pixels, count),
+        ClusterMethod::MedianCut => median_cut(pixels, count),
+        ClusterMethod::Octree => octree(pixels, count),
+    };
+
+    clusters.retain(|c| c.weight > 0.0);
+    clusters.sort_by(|a, b| b.weight.partial_cmp(&a.weight).unwrap());
+    // The octree can't reduce below its eight top-level branches
+    clusters.truncate(count);
+    clusters
+}
+
+/// k-means in OkLab, seeded with farthest-point initialization so results are
+/// deterministic and the seeds cover the whole gamut of the image
+fn kmeans(pixels: &[Color32], count: usize) -> Vec<Cluster> {
+    let points: Vec<[f32; 3]> = pixels
+        .iter()
+        .map(|&p| {
+            let (l, a, b) = rgb_to_oklab(p);
+            [l, a, b]
+        })
+        .collect();
+
+    let distance = |a: &[f32; 3], b: &[f32; 3]| {
+        (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+    };
+
+    // Farthest-point seeding, starting from the mean color
+    let mut mean = [0.0; 3];
+    for p in &points {
+        for c in 0..3 {
+            mean[c] += p[c] / points.len() as f32;
+        }
+    }
+    let mut centers = vec![mean];
+    let mut nearest: Vec<f32> = points.iter().map(|p| distance(p, &mean)).collect();
+    while centers.len() < count {
+        let (index, &farthest) = nearest
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+            .unwrap();
+        if farthest <= 0.0 {
+            break; // Fewer distinct colors than requested
+        }
+        let center = points[index];
+        for (d, p) in nearest.iter_mut().zip(points.iter()) {
+            *d = d.min(distance(p, &center));
+        }
+        centers.push(center);
+    }
+
+    // Lloyd iterations
+    let mut assignment = vec![0usize; points.len()];
+    for _ in 0..KMEANS_ITERATIONS {
+        for (slot, p) in assignment.iter_mut().zip(points.iter()) {
+            *slot = (0..centers.len())
+                .min_by(|&a, &b| distance(p, &centers[a]).partial_cmp(&distance(p, &centers[b])).unwrap())
+                .unwrap();
+        }
+
+        let mut sums = vec![[0.0f32; 3]; centers.len()];
+        let mut counts = vec![0usize; centers.len()];
+        for (&k, p) in assignment.iter().zip(points.iter()) {
+            for c in 0..3 {
+                sums[k][c] += p[c];
+            }
+            counts[k] += 1;
+        }
+        for (k, center) in centers.iter_mut().enumerate() {
+            if counts[k] > 0 {
+                *center = sums[k].map(|s| s / counts[k] as f32);
+            }
+        }
+    }
+
+    let mut counts = vec![0usize; centers.len()];
+    for &k in &assignment {
+        counts[k] += 1;
+    }
+
+    centers
+        .iter()
+        .zip(counts)
+        .map(|(center, n)| Cluster {
+            color: oklab_to_rgb(center[0], center[1], center[2]),
+            weight: n as f32 / points.len() as f32,
+        })
+        .collect()
+}
+
+/// Median cut: repeatedly split the box with the widest channel range at its median
+fn median_cut(pixels: &[Color32], count: usize) -> Vec<Cluster> {
+    let channel = |p: &Color32, c: usize| match c {
+        0 => p.r(),
+        1 => p.g(),
+        _ => p.b(),
+    };
+    let widest_channel = |colors: &[Color32]| {
+        (0..3)
+            .map(|c| {
+                let min = colors.iter().map(|p| channel(p, c)).min().unwrap_or(0);
+                let max = colors.iter().map(|p| channel(p, c)).max().unwrap_or(0);
+                (c, max - min)
+            })
+            .max_by_key(|&(_, range)| range)
+            .unwrap()
+    };
+
+    let mut boxes: Vec<Vec<Color32>> = vec![pixels.to_vec()];
+    while boxes.len() < count {
+        // Split the box with the largest range; stop if every box is a single color
+        let Some((index, c)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| (i, widest_channel(b)))
+            .filter(|(_, (_, range))| *range > 0)
+            .max_by_key(|(_, (_, range))| *range)
+            .map(|(i, (c, _))| (i, c))
+        else {
+            break;
+        };
+
+        let mut colors = boxes.swap_remove(index);
+        colors.sort_by_key(|p| channel(p, c));
+        let upper = colors.split_off(colors.len() / 2);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+
+    boxes
+        .iter()
+        .map(|colors| Cluster {
+            color: average_color(colors),
+            weight: colors.len() as f32 / pixels.len() as f32,
+        })
+        .collect()
+}
+
+/// A node in the quantization octree. Colors are summed so merged nodes
+/// can report their average.
+#[derive(Default)]
+struct OctreeNode {
+    children: [Option<usize>; 8],
+    sum: [u64; 3],
+    pixel_count: usize,
+    is_leaf: bool,
+}
+
+/// Octree quantization: insert every pixel, then fold the deepest, least
+/// populated branches into their parents until only `count` leaves remain
+fn octree(pixels: &[Color32], count: usize) -> Vec<Cluster> {
+    let mut nodes = vec![OctreeNode::default()];
+    // Branch nodes at each level, candidates for reduction
+    let mut levels: Vec<Vec<usize>> = vec![Vec::new(); OCTREE_DEPTH];
+    let mut leaf_count = 0;
+
+    for p in pixels {
+        let mut node = 0;
+        for level in 0..OCTREE_DEPTH {
+            let shift = 7 - level;
+            let child = (((p.r() >> shift) & 1) << 2 | ((p.g() >> shift) & 1) << 1 | ((p.b() >> shift) & 1)) as usize;
+            node = match nodes[node].children[child] {
+                Some(existing) => existing,
+                None => {
+                    nodes.push(OctreeNode::default());
+                    let created = nodes.len() - 1;
+                    nodes[node].children[child] = Some(created);
+                    if level + 1 == OCTREE_DEPTH {
+                        nodes[created].is_leaf = true;
+                        leaf_count += 1;
+                    } else {
+                        levels[level + 1].push(created);
+                    }
+                    created
+                }
+            };
+        }
+        let leaf = &mut nodes[node];
+        leaf.sum[0] += p.r() as u64;
+        leaf.sum[1] += p.g() as u64;
+        leaf.sum[2] += p.b() as u64;
+        leaf.pixel_count += 1;
+    }
+
+    // Reduce from the deepest level upward
+    for level in (1..OCTREE_DEPTH).rev() {
+        let mut candidates = std::mem::take(&mut levels[level]);
+        // Fold the least populated nodes first so large regions keep their detail
+        candidates.sort_by_key(|&n| std::cmp::Reverse(subtree_pixel_count(&nodes, n)));
+        while leaf_count > count {
+            let Some(node) = candidates.pop() else { break };
+            leaf_count -= fold_children(&mut nodes, node);
+            leaf_count += 1;
+        }
+    }
+
+    let mut clusters = Vec::new();
+    collect_leaves(&nodes, 0, pixels.len(), &mut clusters);
+    clusters
+}
+
+fn subtree_pixel_count(nodes: &[OctreeNode], node: usize) -> usize {
+    nodes[node].pixel_count
+        + nodes[node]
+            .children
+            .iter()
+            .flatten()
+            .map(|&child| subtree_pixel_count(nodes, child))
+            .sum::<usize>()
+}
+
+/// Merge all leaves below `node` into it, returning how many leaves were removed.
+/// The caller accounts for `node` itself becoming a leaf.
+fn fold_children(nodes: &mut [OctreeNode], node: usize) -> usize {
+    let mut removed = 0;
+    for slot in 0..8 {
+        let Some(child) = nodes[node].children[slot].take() else { continue };
+        if nodes[child].is_leaf {
+            removed += 1;
+        } else {
+            removed += fold_children(nodes, child);
+        }
+        let (sum, count) = (nodes[child].sum, nodes[child].pixel_count);
+        let parent = &mut nodes[node];
+        for (total, s) in parent.sum.iter_mut().zip(sum) {
+            *total += s;
+        }
+        parent.pixel_count += count;
+    }
+    nodes[node].is_leaf = true;
+    removed
+}
+
+fn collect_leaves(nodes: &[OctreeNode], node: usize, total: usize, out: &mut Vec<Cluster>) {
+    let n = &nodes[node];
+    if n.is_leaf {
+        if n.pixel_count > 0 {
+            let avg = n.sum.map(|s| (s / n.pixel_count as u64) as u8);
+            out.push(Cluster {
+                color: Color32::from_rgb(avg[0], avg[1], avg[2]),
+                weight: n.pixel_count as f32 / total as f32,
+            });
+        }
+        return;
+    }
+    for &child in n.children.iter().flatten() {
+        collect_leaves(nodes, child, total, out);
+    }
+}
+
+fn average_color(colors: &[Color32]) -> Color32 {
+    if colors.is_empty() {
+        return Color32::BLACK;
+    }
+    let mut sum = [0u64; 3];
+    for p in colors {
+        sum[0] += p.r() as u64;
+        sum[1] += p.g() as u64;
+        sum[2] += p.b() as u64;
+    }
+    let n = colors.len() as u64;
+    Color32::from_rgb((sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8)
+}
+
+// =============================================================================
+// Ramp grouping
+// =============================================================================
+
+/// Group clusters into ramps of similar hue. Near-grey clusters form their own
+/// neutral ramp. Each ramp is sorted bright-first; ramps are ordered by hue.
+///
+/// `hue_tolerance` is the largest hue gap (in degrees) allowed between two
+/// neighbouring colors of the same ramp.
+pub fn group_into_ramps(clusters: &[Cluster], hue_tolerance: f32) -> Vec<Vec<Cluster>> {
+    let mut neutrals = Vec::new();
+    let mut chromatic: Vec<(f32, Cluster)> = Vec::new();
+    for &cluster in clusters {
+        let (_, chroma, hue) = rgb_to_oklch(cluster.color);
+        if chroma < NEUTRAL_CHROMA {
+            neutrals.push(cluster);
+        } else {
+            chromatic.push((hue, cluster));
+        }
+    }
+    chromatic.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let mut ramps: Vec<Vec<Cluster>> = Vec::new();
+    if !chromatic.is_empty() {
+        // Start walking the hue circle just after its largest gap, so a ramp
+        // never gets split across the 360° -> 0° seam
+        let n = chromatic.len();
+        let gap_after = |i: usize| {
+            let next = chromatic[(i + 1) % n].0 + if i + 1 == n { 360.0 } else { 0.0 };
+            next - chromatic[i].0
+        };
+        let start = (0..n)
+            .max_by(|&a, &b| gap_after(a).partial_cmp(&gap_after(b)).unwrap())
+            .map_or(0, |i| (i + 1) % n);
+
+        let mut current = vec![chromatic[start].1];
+        for step in 1..n {
+            let i = (start + step) % n;
+            if gap_after((i + n - 1) % n) > hue_tolerance {
+                ramps.push(std::mem::take(&mut current));
+            }
+            current.push(chromatic[i].1);
+        }
+        ramps.push(current);
+    }
+    if !neutrals.is_empty() {
+        ramps.push(neutrals);
+    }
+
+    for ramp in &mut ramps {
+        ramp.sort_by(|a, b| {
+            let la = rgb_to_oklab(a.color).0;
+            let lb = rgb_to_oklab(b.color).0;
+            lb.partial_cmp(&la).unwrap()
+        });
+    }
+    ramps
+}
+
+/// Fit an editable swatch to each ramp. A lone color makes no ramp, so
+/// those are left out.
+pub fn ramps_to_swatches(ramps: &[Vec<Cluster>], color_space: ColorSpace) -> Vec<Swatch> {
+    ramps
+        .iter()
+        .filter(|ramp| ramp.len() >= MIN_SWATCH_SIZE)
+        .map(|ramp| {
+            let targets: Vec<Color32> = ramp.iter().map(|c| c.color).collect();
+            fit_swatch(&targets, color_space, DEFAULT_FIT_TOLERANCE)
+        })
+        .collect()
+}
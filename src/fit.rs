@@ -0,0 +1,407 @@
+use egui_macroquad::egui::Color32;
+
+use crate::color::{delta_e, oklab_to_srgba, srgba_to_oklab, ColorSpace, Srgba};
+use crate::curves::{CurveType, EaseIn, EaseInOut, EaseOut};
+use crate::gamut::{in_gamut, OutputSpace};
+use crate::palette::{ControlPoint, Swatch, MAX_SWATCH_SIZE, MIN_SWATCH_SIZE};
+
+// =============================================================================
+// Swatch fitting: turn a list of target colors into an editable swatch
+// =============================================================================
+//
+// The fitter starts with control points at both ends of the ramp and then
+// greedily pins the step with the largest error until every generated color is
+// within `tolerance` of its target. The result is the smallest set of control
+// points (found this way) that reproduces the targets.
+//
+// The solver goes further, for turning a hand-made ramp into a parametric one:
+// it tries every color space and several curve shapes, and after each pin it
+// nudges the control point colors and positions off the targets to lower the
+// overall error. A smooth ramp then often needs only its two end points.
+
+/// Default maximum ΔE (OkLab) between a generated step and its target
+pub const DEFAULT_FIT_TOLERANCE: f32 = 0.02;
+
+/// Curve exponents the solver starts from, refined afterwards
+const START_EXPONENT: f32 = 2.0;
+const MIN_EXPONENT: f32 = 1.0;
+const MAX_EXPONENT: f32 = 8.0;
+
+/// Initial and final step sizes of the refinement searches
+const COLOR_STEP: (f32, f32) = (0.02, 0.001);
+const POSITION_STEP: (f32, f32) = (0.05, 0.005);
+const EXPONENT_STEP: (f32, f32) = (0.5, 0.02);
+
+/// Refinement sweeps over all control points per pin
+const REFINE_PASSES: usize = 3;
+
+/// Interior control points keep at least this distance from their neighbours
+const MIN_POINT_GAP: f32 = 0.01;
+
+/// Build a swatch whose generated colors approximate `targets`, in order.
+/// Targets are expected bright-first, matching the swatch direction convention.
+/// The swatch size stays within the editor's range: a single target fills
+/// both ends and longer lists are resampled evenly.
+pub fn fit_swatch(targets: &[Color32], color_space: ColorSpace, tolerance: f32) -> Swatch {
+    let targets = Targets::new(&sized_targets(targets));
+    let Some(mut swatch) = end_point_swatch(&targets, color_space, CurveType::default()) else {
+        return Swatch::new(targets.len(), Vec::new(), CurveType::default(), color_space);
+    };
+
+    // Each iteration pins one more step, so this always terminates
+    for _ in 2..targets.len() {
+        if !pin_worst_step(&mut swatch, &targets, tolerance) {
+            break;
+        }
+    }
+    swatch
+}
+
+/// `targets` resampled evenly to at least `MIN_SWATCH_SIZE` and at most
+/// `MAX_SWATCH_SIZE` colors (unless there are none)
+fn sized_targets(targets: &[Color32]) -> Vec<Color32> {
+    let count = targets.len();
+    let size = count.clamp(MIN_SWATCH_SIZE, MAX_SWATCH_SIZE);
+    if count == 0 || size == count {
+        return targets.to_vec();
+    }
+    let scale = (count - 1) as f32 / (size - 1) as f32;
+    (0..size).map(|i| targets[(i as f32 * scale).round() as usize]).collect()
+}
+
+/// Settings for `solve_swatch`
+#[derive(Clone, Debug)]
+pub struct FitOptions {
+    /// Stop adding control points once every step is within this ΔE
+    pub tolerance: f32,
+    /// Most control points the solver may place
+    pub max_control_points: usize,
+    /// Interpolation space to use; None tries all of them
+    pub color_space: Option<ColorSpace>,
+    /// Try ease curves and tune their exponent, instead of keeping linear
+    pub search_curves: bool,
+    /// Move control points off the target colors and steps to lower the error
+    pub refine: bool,
+}
+
+impl Default for FitOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: DEFAULT_FIT_TOLERANCE,
+            max_control_points: 8,
+            color_space: None,
+            search_curves: true,
+            refine: true,
+        }
+    }
+}
+
+/// A solved swatch and how closely it matches the targets
+#[derive(Clone, Debug)]
+pub struct FitResult {
+    pub swatch: Swatch,
+    /// ΔE (OkLab) between each generated step and its target
+    pub residuals: Vec<f32>,
+}
+
+impl FitResult {
+    pub fn max_residual(&self) -> f32 {
+        self.residuals.iter().copied().fold(0.0, f32::max)
+    }
+
+    pub fn mean_residual(&self) -> f32 {
+        if self.residuals.is_empty() {
+            return 0.0;
+        }
+        self.residuals.iter().sum::<f32>() / self.residuals.len() as f32
+    }
+
+    /// Measure the residuals on the fitted steps as they are shown (gamut
+    /// mapped and quantized) instead of as solved
+    pub fn measure_shown(&mut self, targets: &[Color32], shown: &[Color32]) {
+        self.residuals = targets.iter().zip(shown).map(|(&target, &color)| delta_e(target, color)).collect();
+    }
+
+    /// Whether every step is within `tolerance`
+    pub fn fits(&self, tolerance: f32) -> bool {
+        self.max_residual() <= tolerance
+    }
+
+    fn total_error(&self) -> f32 {
+        self.residuals.iter().map(|r| r * r).sum()
+    }
+
+    /// Fits within tolerance beat fits that don't; among those that do, fewer
+    /// control points win, otherwise the lower total error
+    fn is_better_than(&self, other: &FitResult, tolerance: f32) -> bool {
+        match (self.fits(tolerance), other.fits(tolerance)) {
+            (true, false) => true,
+            (false, true) => false,
+            (true, true) => {
+                let points = self.swatch.control_points().len();
+                let other_points = other.swatch.control_points().len();
+                points < other_points || (points == other_points && self.total_error() < other.total_error())
+            }
+            (false, false) => self.total_error() < other.total_error(),
+        }
+    }
+}
+
+/// Choose control points, curve and color space so the swatch's generated
+/// colors match `targets` (bright first) as closely as possible
+pub fn solve_swatch(targets: &[Color32], options: &FitOptions) -> FitResult {
+    let targets = Targets::new(targets);
+    let spaces = match options.color_space {
+        Some(space) => vec![space],
+        None => ColorSpace::ALL.to_vec(),
+    };
+    let curves = if options.search_curves {
+        vec![
+            CurveType::default(),
+            CurveType::EaseIn(EaseIn { exponent: START_EXPONENT }),
+            CurveType::EaseOut(EaseOut { exponent: START_EXPONENT }),
+            CurveType::EaseInOut(EaseInOut { exponent: START_EXPONENT }),
+        ]
+    } else {
+        vec![CurveType::default()]
+    };
+
+    let mut best: Option<FitResult> = None;
+    for &space in &spaces {
+        for &curve in &curves {
+            let result = solve_with(&targets, space, curve, options);
+            if best.as_ref().is_none_or(|best| result.is_better_than(best, options.tolerance)) {
+                best = Some(result);
+            }
+        }
+    }
+
+    let mut best = best.unwrap_or_else(|| FitResult {
+        swatch: Swatch::new(0, Vec::new(), CurveType::default(), spaces[0]),
+        residuals: Vec::new(),
+    });
+    if options.search_curves && tune_exponent(&mut best.swatch, &targets) {
+        if options.refine {
+            refine(&mut best.swatch, &targets);
+        }
+        best.residuals = targets.residuals(&best.swatch);
+    }
+    best
+}
+
+/// Pin and refine control points for one color space and curve
+fn solve_with(targets: &Targets, color_space: ColorSpace, curve: CurveType, options: &FitOptions) -> FitResult {
+    let Some(mut swatch) = end_point_swatch(targets, color_space, curve) else {
+        return FitResult {
+            swatch: Swatch::new(targets.len(), Vec::new(), curve, color_space),
+            residuals: Vec::new(),
+        };
+    };
+
+    loop {
+        if options.refine {
+            refine(&mut swatch, targets);
+        }
+        if swatch.control_points().len() >= options.max_control_points.max(2)
+            || !pin_worst_step(&mut swatch, targets, options.tolerance)
+        {
+            break;
+        }
+    }
+
+    let residuals = targets.residuals(&swatch);
+    FitResult { swatch, residuals }
+}
+
+/// The target colors, with their OkLab coordinates for measuring errors
+struct Targets {
+    colors: Vec<Srgba>,
+    oklab: Vec<(f32, f32, f32)>,
+}
+
+impl Targets {
+    fn new(colors: &[Color32]) -> Self {
+        let colors: Vec<Srgba> = colors.iter().map(|&color| color.into()).collect();
+        let oklab = colors.iter().map(|&color| srgba_to_oklab(color)).collect();
+        Self { colors, oklab }
+    }
+
+    fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Normalized position of a step
+    fn position(&self, index: usize) -> f32 {
+        index as f32 / (self.len() - 1) as f32
+    }
+
+    /// ΔE (OkLab) of each generated step from its target
+    fn residuals(&self, swatch: &Swatch) -> Vec<f32> {
+        swatch
+            .generate_precise_colors()
+            .into_iter()
+            .zip(&self.oklab)
+            .map(|(color, &(l2, a2, b2))| {
+                let (l1, a1, b1) = srgba_to_oklab(color);
+                ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+            })
+            .collect()
+    }
+
+    /// Sum of squared residuals, the quantity refinement minimizes
+    fn error(&self, swatch: &Swatch) -> f32 {
+        self.residuals(swatch).iter().map(|r| r * r).sum()
+    }
+}
+
+/// A swatch with control points on the first and last target, or a single
+/// point for a single target. None if there are no targets.
+fn end_point_swatch(targets: &Targets, color_space: ColorSpace, curve: CurveType) -> Option<Swatch> {
+    let points = match targets.colors[..] {
+        [] => return None,
+        [only] => vec![ControlPoint::new(0, 0.5, only)],
+        [first, .., last] => vec![ControlPoint::new(0, 0.0, first), ControlPoint::new(1, 1.0, last)],
+    };
+    Some(Swatch::new(targets.len(), points, curve, color_space))
+}
+
+/// Pin the target color at the step with the largest error, if that error is
+/// above `tolerance` and the step isn't pinned already. Returns whether a
+/// point was added.
+fn pin_worst_step(swatch: &mut Swatch, targets: &Targets, tolerance: f32) -> bool {
+    let worst = targets
+        .residuals(swatch)
+        .into_iter()
+        .enumerate()
+        .filter(|&(index, _)| swatch.has_control_point_at(targets.position(index), 1e-4).is_none())
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+
+    match worst {
+        Some((index, error)) if error > tolerance => {
+            swatch.add_control_point(targets.position(index), targets.colors[index]);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Pattern search over each control point's OkLab color and (for interior
+/// points) its position, keeping every change that lowers the total error
+fn refine(swatch: &mut Swatch, targets: &Targets) {
+    let mut error = targets.error(swatch);
+    for _ in 0..REFINE_PASSES {
+        let before = error;
+        for index in 0..swatch.control_points().len() {
+            refine_color(swatch, targets, index, &mut error);
+            refine_position(swatch, targets, index, &mut error);
+        }
+        if error >= before * 0.999 {
+            break;
+        }
+    }
+}
+
+fn refine_color(swatch: &mut Swatch, targets: &Targets, index: usize, error: &mut f32) {
+    let mut step = COLOR_STEP.0;
+    while step >= COLOR_STEP.1 {
+        let mut improved = false;
+        for axis in 0..3 {
+            for direction in [step, -step] {
+                let original = swatch.control_points()[index].color;
+                let (l, a, b) = srgba_to_oklab(original);
+                let mut lab = [l, a, b];
+                lab[axis] += direction;
+                // Control points stay displayable colors
+                let candidate = oklab_to_srgba(lab[0], lab[1], lab[2]).with_alpha(original.a);
+                if !in_gamut(candidate, OutputSpace::Srgb) {
+                    continue;
+                }
+                swatch.control_points_mut()[index].color = candidate;
+                let candidate_error = targets.error(swatch);
+                if candidate_error < *error {
+                    *error = candidate_error;
+                    improved = true;
+                } else {
+                    swatch.control_points_mut()[index].color = original;
+                }
+            }
+        }
+        if !improved {
+            step /= 2.0;
+        }
+    }
+}
+
+fn refine_position(swatch: &mut Swatch, targets: &Targets, index: usize, error: &mut f32) {
+    let points = swatch.control_points();
+    // The end points stay at the ends of the ramp
+    if index == 0 || index + 1 >= points.len() {
+        return;
+    }
+    let low = points[index - 1].position + MIN_POINT_GAP;
+    let high = points[index + 1].position - MIN_POINT_GAP;
+
+    let mut step = POSITION_STEP.0;
+    while step >= POSITION_STEP.1 {
+        let mut improved = false;
+        for direction in [step, -step] {
+            let original = swatch.control_points()[index].position;
+            let candidate = original + direction;
+            if !(low..=high).contains(&candidate) {
+                continue;
+            }
+            swatch.control_points_mut()[index].position = candidate;
+            let candidate_error = targets.error(swatch);
+            if candidate_error < *error {
+                *error = candidate_error;
+                improved = true;
+            } else {
+                swatch.control_points_mut()[index].position = original;
+            }
+        }
+        if !improved {
+            step /= 2.0;
+        }
+    }
+}
+
+/// Pattern search over the curve exponent. Returns whether it changed.
+fn tune_exponent(swatch: &mut Swatch, targets: &Targets) -> bool {
+    if curve_exponent(&mut swatch.interpolation_curve).is_none() {
+        return false;
+    }
+
+    let mut error = targets.error(swatch);
+    let mut changed = false;
+    let mut step = EXPONENT_STEP.0;
+    while step >= EXPONENT_STEP.1 {
+        let mut improved = false;
+        for direction in [step, -step] {
+            let original = swatch.interpolation_curve;
+            let Some(value) = curve_exponent(&mut swatch.interpolation_curve) else { return changed };
+            *value = (*value + direction).clamp(MIN_EXPONENT, MAX_EXPONENT);
+            let candidate_error = targets.error(swatch);
+            if candidate_error < error {
+                error = candidate_error;
+                improved = true;
+                changed = true;
+            } else {
+                swatch.interpolation_curve = original;
+            }
+        }
+        if !improved {
+            step /= 2.0;
+        }
+    }
+    changed
+}
+
+/// The exponent parameter of the ease curves
+fn curve_exponent(curve: &mut CurveType) -> Option<&mut f32> {
+    match curve {
+        CurveType::EaseIn(c) => Some(&mut c.exponent),
+        CurveType::EaseOut(c) => Some(&mut c.exponent),
+        CurveType::EaseInOut(c) => Some(&mut c.exponent),
+        CurveType::Linear(_) | CurveType::Bezier(_) => None,
+    }
+}
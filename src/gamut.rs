@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+
+use crate::color::{linear_to_srgb, oklch_to_srgba, srgb_to_linear, srgba_to_oklch, Srgba};
+
+// =============================================================================
+// Output color spaces and gamut mapping
+// =============================================================================
+//
+// Swatches are generated in extended sRGB: interpolating or extrapolating in
+// OkLab and OkLCh can reach colors no sRGB display shows. The palette's output
+// space decides what becomes of them. Generated colors are mapped into its
+// gamut (sRGB, or a wider one like Display P3), and exports write channel
+// values in that space. The canvas itself can only show sRGB, so colors
+// beyond it are drawn clipped and marked.
+
+/// Values this far outside [0, 1] still count as in gamut, absorbing float noise
+const GAMUT_EPSILON: f32 = 1e-4;
+
+/// Bisection steps when reducing chroma, enough for well below one 8-bit level
+const CHROMA_SEARCH_STEPS: u32 = 20;
+
+/// RGB color space that generated colors are mapped into and exported in.
+/// All use the D65 white point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OutputSpace {
+    #[default]
+    Srgb,
+    DisplayP3,
+    Rec2020,
+    AdobeRgb,
+}
+
+impl OutputSpace {
+    pub const ALL: &'static [OutputSpace] = &[
+        OutputSpace::Srgb,
+        OutputSpace::DisplayP3,
+        OutputSpace::Rec2020,
+        OutputSpace::AdobeRgb,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputSpace::Srgb => "sRGB",
+            OutputSpace::DisplayP3 => "Display P3",
+            OutputSpace::Rec2020 => "Rec. 2020",
+            OutputSpace::AdobeRgb => "Adobe RGB",
+        }
+    }
+
+    /// Identifier in CSS `color()`
+    pub fn css_name(&self) -> &'static str {
+        match self {
+            OutputSpace::Srgb => "srgb",
+            OutputSpace::DisplayP3 => "display-p3",
+            OutputSpace::Rec2020 => "rec2020",
+            OutputSpace::AdobeRgb => "a98-rgb",
+        }
+    }
+
+    /// Matrix from linear sRGB to linear light in this space
+    fn srgb_to_space_matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            OutputSpace::Srgb => IDENTITY,
+            OutputSpace::DisplayP3 => [
+                [0.8224619687, 0.1775380313, 0.0],
+                [0.0331941989, 0.9668058011, 0.0],
+                [0.0170826307, 0.0723974407, 0.9105199286],
+            ],
+            OutputSpace::Rec2020 => [
+                [0.6274038959, 0.3292830384, 0.0433130657],
+                [0.0690972894, 0.9195403951, 0.0113623156],
+                [0.0163914389, 0.0880133079, 0.8955952532],
+            ],
+            OutputSpace::AdobeRgb => [
+                [0.7151256069, 0.2848743931, 0.0],
+                [0.0, 1.0, 0.0],
+                [0.0, 0.0411619485, 0.9588380515],
+            ],
+        }
+    }
+
+    /// Inverse of `srgb_to_space_matrix`
+    fn space_to_srgb_matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            OutputSpace::Srgb => IDENTITY,
+            OutputSpace::DisplayP3 => [
+                [1.2249401763, -0.2249401763, 0.0],
+                [-0.0420569547, 1.0420569547, 0.0],
+                [-0.0196375546, -0.0786360456, 1.0982736001],
+            ],
+            OutputSpace::Rec2020 => [
+                [1.6604910021, -0.5876411388, -0.0728498633],
+                [-0.1245504745, 1.1328998971, -0.0083494226],
+                [-0.0181507634, -0.1005788980, 1.1187296614],
+            ],
+            OutputSpace::AdobeRgb => [
+                [1.3983557440, -0.3983557440, 0.0],
+                [0.0, 1.0, 0.0],
+                [0.0, -0.0429289893, 1.0429289893],
+            ],
+        }
+    }
+
+    /// Linear sRGB to linear light in this space
+    pub fn linear_from_srgb(&self, rgb: [f32; 3]) -> [f32; 3] {
+        multiply(self.srgb_to_space_matrix(), rgb)
+    }
+
+    /// Linear light in this space to linear sRGB
+    pub fn linear_to_srgb(&self, rgb: [f32; 3]) -> [f32; 3] {
+        multiply(self.space_to_srgb_matrix(), rgb)
+    }
+
+    /// Transfer function: linear light to an encoded channel value
+    pub fn encode(&self, x: f32) -> f32 {
+        let magnitude = x.abs();
+        let encoded = match self {
+            OutputSpace::Srgb | OutputSpace::DisplayP3 => return linear_to_srgb(x),
+            OutputSpace::Rec2020 if magnitude < REC2020_BETA => 4.5 * magnitude,
+            OutputSpace::Rec2020 => REC2020_ALPHA * magnitude.powf(0.45) - (REC2020_ALPHA - 1.0),
+            OutputSpace::AdobeRgb => magnitude.powf(1.0 / ADOBE_RGB_GAMMA),
+        };
+        encoded.copysign(x)
+    }
+
+    /// Inverse of `encode`
+    pub fn decode(&self, x: f32) -> f32 {
+        let magnitude = x.abs();
+        let linear = match self {
+            OutputSpace::Srgb | OutputSpace::DisplayP3 => return srgb_to_linear(x),
+            OutputSpace::Rec2020 if magnitude < REC2020_BETA * 4.5 => magnitude / 4.5,
+            OutputSpace::Rec2020 => ((magnitude + REC2020_ALPHA - 1.0) / REC2020_ALPHA).powf(1.0 / 0.45),
+            OutputSpace::AdobeRgb => magnitude.powf(ADOBE_RGB_GAMMA),
+        };
+        linear.copysign(x)
+    }
+}
+
+/// Constants of the Rec. 2020 transfer function, as CSS Color 4 uses them
+const REC2020_ALPHA: f32 = 1.099_296_8;
+const REC2020_BETA: f32 = 0.018_053_97;
+
+/// Adobe RGB (1998) is a pure power curve of 563/256
+const ADOBE_RGB_GAMMA: f32 = 563.0 / 256.0;
+
+const IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+fn multiply(m: [[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+/// How colors outside the output gamut are brought into it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GamutMapping {
+    /// Clamp each channel, which can shift hue and lightness
+    #[default]
+    Clip,
+    /// Reduce OkLCh chroma until the color fits, keeping lightness and hue
+    Chroma,
+}
+
+impl GamutMapping {
+    pub const ALL: &'static [GamutMapping] = &[GamutMapping::Clip, GamutMapping::Chroma];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GamutMapping::Clip => "Clip channels",
+            GamutMapping::Chroma => "Reduce chroma",
+        }
+    }
+}
+
+/// Whether an extended sRGB color lies within the gamut of `space`
+pub fn in_gamut(color: Srgba, space: OutputSpace) -> bool {
+    space
+        .linear_from_srgb(color.to_linear())
+        .iter()
+        .all(|&x| (-GAMUT_EPSILON..=1.0 + GAMUT_EPSILON).contains(&x))
+}
+
+/// Map an extended sRGB color into the gamut of `space`. The result holds
+/// the encoded channels of that space, in [0, 1].
+pub fn to_output(color: Srgba, space: OutputSpace, mapping: GamutMapping) -> Srgba {
+    let fitted = match mapping {
+        GamutMapping::Clip => color,
+        GamutMapping::Chroma => reduce_chroma(color, space),
+    };
+    let [r, g, b] = space
+        .linear_from_srgb(fitted.to_linear())
+        .map(|x| space.encode(x.clamp(0.0, 1.0)));
+    Srgba::new(r, g, b, color.a.clamp(0.0, 1.0))
+}
+
+/// An output color (from `to_output`) back in extended sRGB
+pub fn from_output(color: Srgba, space: OutputSpace) -> Srgba {
+    let linear = space.linear_to_srgb([color.r, color.g, color.b].map(|x| space.decode(x)));
+    let [r, g, b] = linear.map(linear_to_srgb);
+    Srgba::new(r, g, b, color.a)
+}
+
+/// The most saturated color of the same OkLCh lightness and hue that fits
+fn reduce_chroma(color: Srgba, space: OutputSpace) -> Srgba {
+    if in_gamut(color, space) {
+        return color;
+    }
+    let (l, c, h) = srgba_to_oklch(color);
+    if l >= 1.0 {
+        return Srgba::WHITE.with_alpha(color.a);
+    }
+    if l <= 0.0 {
+        return Srgba::BLACK.with_alpha(color.a);
+    }
+
+    let (mut low, mut high) = (0.0, c);
+    for _ in 0..CHROMA_SEARCH_STEPS {
+        let middle = (low + high) / 2.0;
+        if in_gamut(oklch_to_srgba(l, middle, h), space) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    oklch_to_srgba(l, low, h).with_alpha(color.a)
+}
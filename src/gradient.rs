@@ -0,0 +1,88 @@
+use egui_macroquad::egui::Color32;
+
+use crate::color::{delta_e, lerp_color, ColorSpace};
+use crate::palette::Swatch;
+
+// =============================================================================
+// Continuous gradients: approximate a swatch with interpolated stops
+// =============================================================================
+//
+// CSS, SVG and GIMP gradients interpolate linearly between stops (in sRGB,
+// unless CSS is told otherwise), while a swatch follows its curve and may
+// extrapolate past its control points. The swatch is approximated by adding
+// stops where the straight interpolation strays from the real gradient by
+// more than a tolerance.
+
+/// Default approximation error, as a ΔE (OkLab distance)
+pub const DEFAULT_GRADIENT_TOLERANCE: f32 = 0.01;
+
+/// Segments are never split deeper than this, so each span between control
+/// points adds at most 256 stops
+const MAX_DEPTH: u32 = 8;
+
+/// Points checked inside each segment, as fractions of its length
+const PROBES: [f32; 3] = [0.25, 0.5, 0.75];
+
+/// A gradient stop at `position` in [0.0, 1.0]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientStop {
+    pub position: f32,
+    pub color: Color32,
+}
+
+/// Stops that reproduce `swatch` within `tolerance` when a consumer
+/// interpolates between them in `space`. Control points always get a stop, since
+/// the curve can bend sharply there.
+pub fn gradient_stops(swatch: &Swatch, space: ColorSpace, tolerance: f32) -> Vec<GradientStop> {
+    let mut breaks = vec![0.0, 1.0];
+    breaks.extend(
+        swatch
+            .control_points()
+            .iter()
+            .map(|cp| cp.position)
+            .filter(|&p| p > 0.0 && p < 1.0),
+    );
+    breaks.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    breaks.dedup();
+
+    let stop = |position: f32| GradientStop {
+        position,
+        color: swatch.sample_at(position),
+    };
+    let mut stops = vec![stop(breaks[0])];
+    for pair in breaks.windows(2) {
+        let end = stop(pair[1]);
+        subdivide(swatch, space, tolerance, *stops.last().unwrap(), end, 0, &mut stops);
+    }
+    stops
+}
+
+/// Push the stops after `start`, up to and including `end`
+fn subdivide(
+    swatch: &Swatch,
+    space: ColorSpace,
+    tolerance: f32,
+    start: GradientStop,
+    end: GradientStop,
+    depth: u32,
+    stops: &mut Vec<GradientStop>,
+) {
+    let within = PROBES.iter().all(|&f| {
+        let position = start.position + (end.position - start.position) * f;
+        let approximation = lerp_color(start.color, end.color, f, space);
+        delta_e(approximation, swatch.sample_at(position)) <= tolerance
+    });
+
+    if within || depth >= MAX_DEPTH {
+        stops.push(end);
+        return;
+    }
+
+    let position = (start.position + end.position) / 2.0;
+    let middle = GradientStop {
+        position,
+        color: swatch.sample_at(position),
+    };
+    subdivide(swatch, space, tolerance, start, middle, depth + 1, stops);
+    subdivide(swatch, space, tolerance, middle, end, depth + 1, stops);
+}
@@ -0,0 +1,158 @@
+use std::fs::File;
+use std::path::Path;
+
+use egui_macroquad::egui::Color32;
+
+use crate::image_io::SourceImage;
+
+// =============================================================================
+// Gradient mapping: recolor grayscale or indexed sprites with a swatch
+// =============================================================================
+//
+// Each pixel of the sprite is reduced to a "level" in [0, level_count), which
+// selects a color from the swatch. Level 0 maps to the first swatch color
+// (position 0 = bright), the highest level to the last one. For grayscale
+// sprites the level is the inverted luminance, so light pixels pick light
+// colors; for indexed sprites it is the palette index.
+
+/// What a sprite pixel's level is read from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GradientSource {
+    #[default]
+    Luminance,
+    Index,
+}
+
+impl GradientSource {
+    pub const ALL: &'static [GradientSource] = &[GradientSource::Luminance, GradientSource::Index];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GradientSource::Luminance => "Luminance",
+            GradientSource::Index => "Palette index",
+        }
+    }
+}
+
+/// A sprite reduced to one level per pixel, ready to be gradient mapped
+#[derive(Clone)]
+pub struct GradientMapInput {
+    pub width: usize,
+    pub height: usize,
+    /// Level of each pixel, in row-major order
+    levels: Vec<u8>,
+    /// Alpha of each pixel, carried over to the output
+    alpha: Vec<u8>,
+    /// Number of distinct levels (256 for luminance, palette size for indices)
+    level_count: usize,
+}
+
+impl GradientMapInput {
+    /// Load a sprite from disk, reading levels from luminance or palette indices
+    pub fn load(path: impl AsRef<Path>, source: GradientSource) -> Result<Self, String> {
+        match source {
+            GradientSource::Luminance => SourceImage::load(path)
+                .map(|image| Self::from_luminance(&image))
+                .map_err(|err| err.to_string()),
+            GradientSource::Index => Self::load_indexed_png(path),
+        }
+    }
+
+    pub fn from_luminance(image: &SourceImage) -> Self {
+        let levels = image
+            .pixels
+            .iter()
+            .map(|p| {
+                // Rec. 709 luma on the stored (gamma-encoded) values
+                let luma = 0.2126 * p.r() as f32 + 0.7152 * p.g() as f32 + 0.0722 * p.b() as f32;
+                255 - luma.round().clamp(0.0, 255.0) as u8
+            })
+            .collect();
+
+        Self {
+            width: image.width,
+            height: image.height,
+            levels,
+            alpha: image.pixels.iter().map(|p| p.a()).collect(),
+            level_count: 256,
+        }
+    }
+
+    /// Read the raw palette indices of an indexed PNG
+    fn load_indexed_png(path: impl AsRef<Path>) -> Result<Self, String> {
+        let file = File::open(path).map_err(|err| err.to_string())?;
+        let mut decoder = png::Decoder::new(file);
+        // Keep the indices instead of expanding them to RGB
+        decoder.set_transformations(png::Transformations::IDENTITY);
+        let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut buffer).map_err(|err| err.to_string())?;
+        if frame.color_type != png::ColorType::Indexed {
+            return Err("Not an indexed PNG".to_string());
+        }
+
+        let info = reader.info();
+        let level_count = info.palette.as_ref().map_or(256, |p| p.len() / 3);
+        let transparency = info.trns.as_ref().map(|t| t.to_vec()).unwrap_or_default();
+
+        let bits = frame.bit_depth as usize;
+        let mask = ((1u16 << bits) - 1) as u8;
+        let (width, height) = (frame.width as usize, frame.height as usize);
+
+        let mut levels = Vec::with_capacity(width * height);
+        for row in buffer.chunks(frame.line_size).take(height) {
+            for x in 0..width {
+                let bit_offset = x * bits;
+                let shift = 8 - bits - bit_offset % 8;
+                levels.push((row[bit_offset / 8] >> shift) & mask);
+            }
+        }
+        // Indices without a tRNS entry are opaque
+        let alpha = levels
+            .iter()
+            .map(|&i| transparency.get(i as usize).copied().unwrap_or(255))
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            levels,
+            alpha,
+            level_count,
+        })
+    }
+}
+
+/// Recolor `input` with the colors of a swatch. With `invert`, level 0 maps
+/// to the last color instead of the first.
+pub fn gradient_map(input: &GradientMapInput, ramp: &[Color32], invert: bool) -> SourceImage {
+    let pixels = input
+        .levels
+        .iter()
+        .zip(input.alpha.iter())
+        .map(|(&level, &alpha)| {
+            let Some(&last) = ramp.last() else {
+                return Color32::TRANSPARENT;
+            };
+            let t = if input.level_count > 1 {
+                (level as f32 / (input.level_count - 1) as f32).min(1.0)
+            } else {
+                0.0
+            };
+            let t = if invert { 1.0 - t } else { t };
+            let color = ramp
+                .get((t * (ramp.len() - 1) as f32).round() as usize)
+                .copied()
+                .unwrap_or(last);
+            let [r, g, b, _] = color.to_srgba_unmultiplied();
+            Color32::from_rgba_unmultiplied(r, g, b, alpha)
+        })
+        .collect();
+
+    SourceImage {
+        width: input.width,
+        height: input.height,
+        pixels,
+    }
+}
@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use egui_macroquad::egui::Color32;
+
+// =============================================================================
+// SourceImage: An RGBA image loaded from (or written to) disk
+// =============================================================================
+
+/// Upper bound on the number of pixels returned by `sampled_pixels`
+const MAX_SAMPLED_PIXELS: usize = 65_536;
+/// Pixels more transparent than this are ignored when sampling
+const MIN_ALPHA: u8 = 128;
+
+/// An image held as unmultiplied `Color32` pixels
+#[derive(Clone)]
+pub struct SourceImage {
+    pub width: usize,
+    pub height: usize,
+    /// Pixels in row-major order
+    pub pixels: Vec<Color32>,
+}
+
+impl SourceImage {
+    /// Load a PNG or JPEG from disk
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, image::ImageError> {
+        let rgba = image::open(path)?.to_rgba8();
+        let pixels = rgba
+            .pixels()
+            .map(|p| Color32::from_rgba_unmultiplied(p[0], p[1], p[2], p[3]))
+            .collect();
+
+        Ok(Self {
+            width: rgba.width() as usize,
+            height: rgba.height() as usize,
+            pixels,
+        })
+    }
+
+    /// Save as PNG (or any format `image` infers from the extension)
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), image::ImageError> {
+        image::RgbaImage::from_raw(self.width as u32, self.height as u32, self.to_rgba_bytes())
+            .expect("pixel buffer matches image dimensions")
+            .save(path)
+    }
+
+    /// Pixels as a flat, unmultiplied RGBA byte buffer
+    pub fn to_rgba_bytes(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|c| c.to_srgba_unmultiplied()).collect()
+    }
+
+    /// Opaque pixels, evenly subsampled down to at most `MAX_SAMPLED_PIXELS`
+    pub fn sampled_pixels(&self) -> Vec<Color32> {
+        let stride = self.pixels.len().div_ceil(MAX_SAMPLED_PIXELS).max(1);
+        self.pixels
+            .iter()
+            .step_by(stride)
+            .filter(|p| p.a() >= MIN_ALPHA)
+            .map(|p| Color32::from_rgb(p.r(), p.g(), p.b()))
+            .collect()
+    }
+}
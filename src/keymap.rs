@@ -0,0 +1,408 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use egui_macroquad::egui::{Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::project::write_atomically;
+use crate::settings::data_dir;
+
+// =============================================================================
+// Keymap: keyboard shortcuts for editor actions
+// =============================================================================
+//
+// Every keyboard-driven operation is an `Action`. The keymap binds each action
+// to zero or more shortcuts; the defaults below can be remapped by a
+// `keymap.json` file in the data directory, which maps action ids to shortcut
+// strings:
+//
+//     { "save": "Ctrl+S", "zoom_in": ["Ctrl+Plus", "Ctrl+Equals"], "pin_color": [] }
+//
+// Actions missing from the file keep their defaults; an empty list unbinds.
+
+const KEYMAP_FILE: &str = "keymap.json";
+
+/// An editor operation that can be bound to a shortcut
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    SelectPreviousSwatch,
+    SelectNextSwatch,
+    SelectPreviousColor,
+    SelectNextColor,
+    AddSwatch,
+    DuplicateSwatch,
+    RemoveSwatch,
+    MoveSwatchUp,
+    MoveSwatchDown,
+    AddControlPoint,
+    RemoveControlPoint,
+    PinColor,
+    ToggleStepLock,
+    CycleColorSpace,
+    CycleCurve,
+    CopyColorHex,
+    CopyColorRgb,
+    CopyColorOklch,
+    CopySwatchColors,
+    CopySwatch,
+    CopyPalette,
+    Paste,
+    ZoomIn,
+    ZoomOut,
+    ResetView,
+    ZoomToFit,
+    FrameSelectedSwatch,
+    CycleLayout,
+    CompareBackgrounds,
+    NewPalette,
+    OpenPalette,
+    SavePalette,
+    SavePaletteAs,
+    Export,
+    ExportLookupTextures,
+    FitSwatch,
+    ExtractFromImage,
+    PreviewOnImage,
+    GradientMapSprite,
+    ShowShortcuts,
+    CommandPalette,
+    Exit,
+}
+
+impl Action {
+    pub const ALL: &'static [Action] = &[
+        Action::SelectPreviousSwatch,
+        Action::SelectNextSwatch,
+        Action::SelectPreviousColor,
+        Action::SelectNextColor,
+        Action::AddSwatch,
+        Action::DuplicateSwatch,
+        Action::RemoveSwatch,
+        Action::MoveSwatchUp,
+        Action::MoveSwatchDown,
+        Action::AddControlPoint,
+        Action::RemoveControlPoint,
+        Action::PinColor,
+        Action::ToggleStepLock,
+        Action::CycleColorSpace,
+        Action::CycleCurve,
+        Action::CopyColorHex,
+        Action::CopyColorRgb,
+        Action::CopyColorOklch,
+        Action::CopySwatchColors,
+        Action::CopySwatch,
+        Action::CopyPalette,
+        Action::Paste,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ResetView,
+        Action::ZoomToFit,
+        Action::FrameSelectedSwatch,
+        Action::CycleLayout,
+        Action::CompareBackgrounds,
+        Action::NewPalette,
+        Action::OpenPalette,
+        Action::SavePalette,
+        Action::SavePaletteAs,
+        Action::Export,
+        Action::ExportLookupTextures,
+        Action::FitSwatch,
+        Action::ExtractFromImage,
+        Action::PreviewOnImage,
+        Action::GradientMapSprite,
+        Action::ShowShortcuts,
+        Action::CommandPalette,
+        Action::Exit,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::SelectPreviousSwatch => "Select Previous Swatch",
+            Action::SelectNextSwatch => "Select Next Swatch",
+            Action::SelectPreviousColor => "Select Previous Color",
+            Action::SelectNextColor => "Select Next Color",
+            Action::AddSwatch => "Add Swatch",
+            Action::DuplicateSwatch => "Duplicate Swatch",
+            Action::RemoveSwatch => "Remove Swatch",
+            Action::MoveSwatchUp => "Move Swatch Up",
+            Action::MoveSwatchDown => "Move Swatch Down",
+            Action::AddControlPoint => "Add Control Point at Selected Color",
+            Action::RemoveControlPoint => "Remove Control Point at Selected Color",
+            Action::PinColor => "Pin Edited Color",
+            Action::ToggleStepLock => "Lock/Unlock Selected Step",
+            Action::CycleColorSpace => "Cycle Color Space",
+            Action::CycleCurve => "Cycle Curve Type",
+            Action::CopyColorHex => "Copy Color as Hex",
+            Action::CopyColorRgb => "Copy Color as rgb()",
+            Action::CopyColorOklch => "Copy Color as oklch()",
+            Action::CopySwatchColors => "Copy Swatch as Hex List",
+            Action::CopySwatch => "Copy Swatch",
+            Action::CopyPalette => "Copy Palette",
+            Action::Paste => "Paste",
+            Action::ZoomIn => "Zoom In",
+            Action::ZoomOut => "Zoom Out",
+            Action::ResetView => "Reset Viewport",
+            Action::ZoomToFit => "Zoom to Fit",
+            Action::FrameSelectedSwatch => "Frame Selected Swatch",
+            Action::CycleLayout => "Cycle Layout Mode",
+            Action::CompareBackgrounds => "Compare on Backgrounds",
+            Action::NewPalette => "New Palette",
+            Action::OpenPalette => "Open Palette",
+            Action::SavePalette => "Save Palette",
+            Action::SavePaletteAs => "Save Palette As",
+            Action::Export => "Export",
+            Action::ExportLookupTextures => "Export Lookup Textures",
+            Action::FitSwatch => "Fit Swatch to Colors",
+            Action::ExtractFromImage => "Extract from Image",
+            Action::PreviewOnImage => "Preview on Image",
+            Action::GradientMapSprite => "Gradient Map Sprite",
+            Action::ShowShortcuts => "Keyboard Shortcuts",
+            Action::CommandPalette => "Command Palette",
+            Action::Exit => "Exit",
+        }
+    }
+
+    /// Identifier used in the keymap file
+    pub fn id(&self) -> &'static str {
+        match self {
+            Action::SelectPreviousSwatch => "select_previous_swatch",
+            Action::SelectNextSwatch => "select_next_swatch",
+            Action::SelectPreviousColor => "select_previous_color",
+            Action::SelectNextColor => "select_next_color",
+            Action::AddSwatch => "add_swatch",
+            Action::DuplicateSwatch => "duplicate_swatch",
+            Action::RemoveSwatch => "remove_swatch",
+            Action::MoveSwatchUp => "move_swatch_up",
+            Action::MoveSwatchDown => "move_swatch_down",
+            Action::AddControlPoint => "add_control_point",
+            Action::RemoveControlPoint => "remove_control_point",
+            Action::PinColor => "pin_color",
+            Action::ToggleStepLock => "toggle_step_lock",
+            Action::CycleColorSpace => "cycle_color_space",
+            Action::CycleCurve => "cycle_curve",
+            Action::CopyColorHex => "copy_color_hex",
+            Action::CopyColorRgb => "copy_color_rgb",
+            Action::CopyColorOklch => "copy_color_oklch",
+            Action::CopySwatchColors => "copy_swatch_colors",
+            Action::CopySwatch => "copy_swatch",
+            Action::CopyPalette => "copy_palette",
+            Action::Paste => "paste",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+            Action::ResetView => "reset_view",
+            Action::ZoomToFit => "zoom_to_fit",
+            Action::FrameSelectedSwatch => "frame_selected_swatch",
+            Action::CycleLayout => "cycle_layout",
+            Action::CompareBackgrounds => "compare_backgrounds",
+            Action::NewPalette => "new_palette",
+            Action::OpenPalette => "open_palette",
+            Action::SavePalette => "save",
+            Action::SavePaletteAs => "save_as",
+            Action::Export => "export",
+            Action::ExportLookupTextures => "export_lookup_textures",
+            Action::FitSwatch => "fit_swatch",
+            Action::ExtractFromImage => "extract_from_image",
+            Action::PreviewOnImage => "preview_on_image",
+            Action::GradientMapSprite => "gradient_map_sprite",
+            Action::ShowShortcuts => "show_shortcuts",
+            Action::CommandPalette => "command_palette",
+            Action::Exit => "exit",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Action> {
+        Self::ALL.iter().copied().find(|action| action.id() == id)
+    }
+
+    fn default_shortcuts(&self) -> &'static [&'static str] {
+        match self {
+            Action::SelectPreviousSwatch => &["Up"],
+            Action::SelectNextSwatch => &["Down"],
+            Action::SelectPreviousColor => &["Left"],
+            Action::SelectNextColor => &["Right"],
+            Action::AddSwatch => &["Shift+A"],
+            Action::DuplicateSwatch => &["Ctrl+D"],
+            Action::RemoveSwatch => &["Shift+Delete"],
+            Action::MoveSwatchUp => &["Alt+Up"],
+            Action::MoveSwatchDown => &["Alt+Down"],
+            Action::AddControlPoint => &["Insert"],
+            Action::RemoveControlPoint => &["Delete"],
+            Action::PinColor => &["Enter"],
+            Action::ToggleStepLock => &["K"],
+            Action::CycleColorSpace => &["C"],
+            Action::CycleCurve => &["V"],
+            Action::CopyColorHex => &["Ctrl+C"],
+            Action::CopyColorRgb | Action::CopyColorOklch | Action::CopyPalette => &[],
+            Action::CopySwatchColors => &["Ctrl+Shift+C"],
+            Action::CopySwatch => &["Ctrl+Alt+C"],
+            Action::Paste => &["Ctrl+V"],
+            Action::ZoomIn => &["Ctrl+Plus", "Ctrl+Equals"],
+            Action::ZoomOut => &["Ctrl+Minus"],
+            Action::ResetView => &["Ctrl+0"],
+            Action::ZoomToFit => &["Home"],
+            Action::FrameSelectedSwatch => &["F"],
+            Action::CycleLayout => &["L"],
+            Action::CompareBackgrounds => &[],
+            Action::NewPalette => &["Ctrl+N"],
+            Action::OpenPalette => &["Ctrl+O"],
+            Action::SavePalette => &["Ctrl+S"],
+            Action::SavePaletteAs => &["Ctrl+Shift+S"],
+            Action::Export => &["Ctrl+E"],
+            Action::ExportLookupTextures
+            | Action::FitSwatch
+            | Action::ExtractFromImage
+            | Action::PreviewOnImage
+            | Action::GradientMapSprite
+            | Action::ShowShortcuts => &[],
+            Action::CommandPalette => &["Ctrl+P"],
+            Action::Exit => &["Ctrl+Q"],
+        }
+    }
+}
+
+/// Parse `Ctrl+Shift+S`-style text. `Ctrl` and `Cmd` both mean the platform's
+/// command key; key names are egui's (`Up`, `Delete`, `Plus`, `A`, `0`...).
+pub fn parse_shortcut(text: &str) -> Option<KeyboardShortcut> {
+    let mut modifiers = Modifiers::NONE;
+    let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+    // "Ctrl++" ends with an empty part for the plus key
+    if text.trim_end().ends_with("++") {
+        parts.truncate(parts.len() - 2);
+        parts.push("Plus");
+    }
+    let (key, modifier_names) = parts.split_last()?;
+    for name in modifier_names {
+        match name.to_ascii_lowercase().as_str() {
+            "ctrl" | "cmd" | "command" => modifiers |= Modifiers::COMMAND,
+            "shift" => modifiers |= Modifiers::SHIFT,
+            "alt" | "option" => modifiers |= Modifiers::ALT,
+            _ => return None,
+        }
+    }
+    Some(KeyboardShortcut::new(modifiers, Key::from_name(key)?))
+}
+
+/// Inverse of `parse_shortcut`
+pub fn shortcut_text(shortcut: &KeyboardShortcut) -> String {
+    let mut text = String::new();
+    if shortcut.modifiers.command {
+        text += "Ctrl+";
+    }
+    if shortcut.modifiers.shift {
+        text += "Shift+";
+    }
+    if shortcut.modifiers.alt {
+        text += "Alt+";
+    }
+    text += shortcut.logical_key.name();
+    text
+}
+
+/// One shortcut or a list of them, as written in the keymap file
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum BindingEntry {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Shortcut bindings for every action
+pub struct Keymap {
+    bindings: BTreeMap<Action, Vec<KeyboardShortcut>>,
+    /// Problems found in the keymap file, for display
+    pub errors: Vec<String>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .iter()
+            .map(|&action| {
+                let shortcuts = action.default_shortcuts().iter().filter_map(|s| parse_shortcut(s)).collect();
+                (action, shortcuts)
+            })
+            .collect();
+        Self {
+            bindings,
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl Keymap {
+    pub fn path() -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join(KEYMAP_FILE))
+    }
+
+    /// The default keymap with the user's keymap file applied on top
+    pub fn load() -> Self {
+        let mut keymap = Self::default();
+        let Some(text) = Self::path().and_then(|path| fs::read_to_string(path).ok()) else {
+            return keymap;
+        };
+
+        let entries: BTreeMap<String, BindingEntry> = match serde_json::from_str(&text) {
+            Ok(entries) => entries,
+            Err(err) => {
+                keymap.errors.push(format!("{KEYMAP_FILE}: {err}"));
+                return keymap;
+            }
+        };
+        for (id, entry) in entries {
+            let Some(action) = Action::from_id(&id) else {
+                keymap.errors.push(format!("Unknown action \"{id}\""));
+                continue;
+            };
+            let texts = match entry {
+                BindingEntry::One(text) => vec![text],
+                BindingEntry::Many(texts) => texts,
+            };
+            let mut shortcuts = Vec::new();
+            for text in texts {
+                match parse_shortcut(&text) {
+                    Some(shortcut) => shortcuts.push(shortcut),
+                    None => keymap.errors.push(format!("Invalid shortcut \"{text}\" for {id}")),
+                }
+            }
+            keymap.bindings.insert(action, shortcuts);
+        }
+        keymap
+    }
+
+    /// Write the current bindings as a keymap file, to be edited by hand
+    pub fn save(&self) -> std::io::Result<PathBuf> {
+        let path = Self::path().ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no data directory"))?;
+        let entries: BTreeMap<&str, BindingEntry> = self
+            .bindings
+            .iter()
+            .map(|(action, shortcuts)| {
+                let texts = shortcuts.iter().map(shortcut_text).collect();
+                (action.id(), BindingEntry::Many(texts))
+            })
+            .collect();
+        let text = serde_json::to_string_pretty(&entries).map_err(std::io::Error::other)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_atomically(&path, &text)?;
+        Ok(path)
+    }
+
+    pub fn shortcuts(&self, action: Action) -> &[KeyboardShortcut] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every binding, most specific first, which is the order shortcuts must
+    /// be matched in (so Ctrl+Shift+S isn't taken by Ctrl+S)
+    pub fn bindings_by_specificity(&self) -> Vec<(Action, KeyboardShortcut)> {
+        let mut bindings: Vec<(Action, KeyboardShortcut)> = self
+            .bindings
+            .iter()
+            .flat_map(|(&action, shortcuts)| shortcuts.iter().map(move |&shortcut| (action, shortcut)))
+            .collect();
+        let modifier_count = |m: Modifiers| m.command as u8 + m.shift as u8 + m.alt as u8;
+        bindings.sort_by_key(|(_, shortcut)| std::cmp::Reverse(modifier_count(shortcut.modifiers)));
+        bindings
+    }
+}
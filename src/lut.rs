@@ -0,0 +1,90 @@
+use egui_macroquad::egui::Color32;
+
+use crate::color::ColorSpace;
+use crate::image_io::SourceImage;
+use crate::mapping::PaletteMatcher;
+use crate::palette::{Palette, Swatch};
+
+// =============================================================================
+// Lookup textures for shaders
+// =============================================================================
+//
+// Ramp textures sample a swatch's gradient continuously across their width,
+// so a shader can index them with any value in [0, 1] instead of only the
+// `size` generated steps. Column 0 is the bright end (position 0).
+//
+// A 3D LUT maps every RGB input to its nearest palette color, quantizing an
+// image to the palette in a single texture lookup.
+
+/// Default width of ramp textures
+pub const DEFAULT_RAMP_WIDTH: usize = 256;
+
+/// Default edge length of 3D LUTs (the common size for .cube files)
+pub const DEFAULT_LUT_SIZE: usize = 33;
+
+/// Largest edge length offered; 64 is the usual .cube maximum and keeps the
+/// synchronous export at a quarter million lines
+pub const MAX_LUT_SIZE: usize = 64;
+
+/// The colors of one ramp row, `width` samples across the whole gradient,
+/// gamut mapped like the palette's generated colors
+fn ramp_row(palette: &Palette, swatch: &Swatch, width: usize) -> Vec<Color32> {
+    (0..width)
+        .map(|x| {
+            let t = if width > 1 { x as f32 / (width - 1) as f32 } else { 0.5 };
+            palette.swatch_display_sample(swatch, t)
+        })
+        .collect()
+}
+
+/// A `width` x `height` texture of one swatch's gradient
+pub fn ramp_texture(palette: &Palette, swatch: &Swatch, width: usize, height: usize) -> SourceImage {
+    let row = ramp_row(palette, swatch, width);
+    SourceImage {
+        width,
+        height,
+        pixels: row.iter().copied().cycle().take(width * height).collect(),
+    }
+}
+
+/// Every swatch's ramp stacked top to bottom, `row_height` pixels each, in
+/// palette order
+pub fn ramp_atlas(palette: &Palette, width: usize, row_height: usize) -> SourceImage {
+    let mut pixels = Vec::with_capacity(width * row_height * palette.swatches.len());
+    for swatch in &palette.swatches {
+        let row = ramp_row(palette, swatch, width);
+        for _ in 0..row_height {
+            pixels.extend_from_slice(&row);
+        }
+    }
+    SourceImage {
+        width,
+        height: row_height * palette.swatches.len(),
+        pixels,
+    }
+}
+
+/// A .cube 3D LUT of edge length `size` that replaces every color by its
+/// nearest palette color, matched in `space`
+pub fn cube_lut(colors: &[Color32], size: usize, space: ColorSpace, title: &str) -> String {
+    let size = size.clamp(2, MAX_LUT_SIZE);
+    let matcher = PaletteMatcher::new(colors, space);
+    let level = |i: usize| (i as f32 * 255.0 / (size - 1) as f32).round() as u8;
+
+    let mut out = format!("TITLE \"{title}\"\nLUT_3D_SIZE {size}\nDOMAIN_MIN 0.0 0.0 0.0\nDOMAIN_MAX 1.0 1.0 1.0\n");
+    // Red varies fastest, then green, then blue
+    for b in 0..size {
+        for g in 0..size {
+            for r in 0..size {
+                let [r, g, b, _] = matcher.nearest(Color32::from_rgb(level(r), level(g), level(b))).to_srgba_unmultiplied();
+                out += &format!(
+                    "{:.6} {:.6} {:.6}\n",
+                    r as f32 / 255.0,
+                    g as f32 / 255.0,
+                    b as f32 / 255.0
+                );
+            }
+        }
+    }
+    out
+}
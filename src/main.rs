@@ -1,42 +1,127 @@
 use macroquad::prelude::*;
 
 mod app;
+mod autosave;
+mod background;
+mod cli;
+mod clipboard;
 mod color;
 mod curves;
+mod export;
+mod extract;
+mod fit;
+mod gamut;
+mod gradient;
+mod gradient_map;
+mod image_io;
+mod keymap;
+mod lut;
+mod mapping;
 mod palette;
+mod project;
 mod rendering;
+mod settings;
+mod templates;
 mod ui;
 mod viewport;
 
 use app::App;
-use rendering::{draw_checker_background, draw_palette};
+use autosave::Autosave;
+use background::{draw_background, BackgroundImage};
+use keymap::Keymap;
+use rendering::{draw_drop_indicator, draw_palette, StepMarkers};
+use settings::Settings;
+use ui::canvas::CanvasState;
+use ui::command_palette::CommandPaletteState;
+use ui::export_window::ExportState;
+use ui::file_dialogs::{FileDialogState, FileRequest};
+use ui::gradient_map::GradientMapState;
+use ui::image_extract::ImageExtractState;
+use ui::lut_export::LutExportState;
 use ui::swatch_editor::SwatchEditorState;
+use ui::swatch_fit::SwatchFitState;
 use ui::palette_editor::PaletteEditorState;
-use ui::{draw_palette_editor, draw_swatch_editor, draw_top_panel};
+use ui::palette_preview::PalettePreviewState;
+use ui::{
+    draw_background_compare, draw_canvas_context_menu, draw_command_palette, draw_export_window, draw_file_dialogs, draw_gradient_map, draw_image_extract, draw_lut_export, draw_palette_editor,
+    draw_palette_preview, draw_recovery_prompt, draw_shortcuts_window, draw_swatch_editor, draw_swatch_fit, draw_top_panel,
+    handle_canvas_input, handle_shortcuts, visible_canvas_area, OpenWindows,
+};
 
 // =============================================================================
 // Main application
 // =============================================================================
 
-#[macroquad::main("Palette Helper")]
-async fn main() {
+fn main() {
+    // With arguments, run a headless command instead of opening the editor
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
+    macroquad::Window::new("Palette Helper", run_editor());
+}
+
+async fn run_editor() {
     let mut app = App::new();
     let mut swatch_editor_state = SwatchEditorState::default();
     let mut palette_editor_state = PaletteEditorState::default();
+    let mut swatch_fit_state = SwatchFitState::default();
+    let mut image_extract_state = ImageExtractState::default();
+    let mut palette_preview_state = PalettePreviewState::default();
+    let mut gradient_map_state = GradientMapState::default();
+    let mut export_state = ExportState::default();
+    let mut lut_export_state = LutExportState::default();
+    let mut command_palette_state = CommandPaletteState::default();
+    let mut canvas_state = CanvasState::default();
+    let mut background_image = BackgroundImage::default();
+    let mut open_windows = OpenWindows::default();
+    let mut file_dialog_state = FileDialogState::default();
+    let mut settings = Settings::load();
+    let mut keymap = Keymap::load();
+    let (mut autosave, mut recovered) = Autosave::start(get_time());
 
     // Sync editor state with initial swatch
     swatch_editor_state.sync_with_swatch(&app);
 
+    // Closing the window goes through File > Exit, which asks to save changes
+    prevent_quit();
+
     loop {
-        // Draw background with parallax
-        draw_checker_background(&app.viewport);
+        if is_quit_requested() {
+            open_windows.file_request = Some(FileRequest::Exit);
+        }
+        if file_dialog_state.quit {
+            autosave.finish();
+            break;
+        }
 
-        // Draw all palette swatches (auto-aligned)
+        // Draw the chosen background (the checker scrolls with parallax)
+        background_image.sync(&settings.background);
+        draw_background(&app.viewport, &settings.background, &background_image);
+
+        // Draw all palette swatches (auto-aligned), marking locked steps and
+        // colors beyond sRGB (only wide-gamut output spaces have those)
+        let markers: Vec<StepMarkers> = (0..app.palette.swatches.len())
+            .map(|index| {
+                let swatch = &app.palette.swatches[index];
+                StepMarkers {
+                    outside_srgb: app.palette.outside_srgb(index),
+                    locked: (0..swatch.size).map(|step| swatch.is_step_locked(step)).collect(),
+                }
+            })
+            .collect();
         draw_palette(
             &app.viewport,
+            &app.layout,
             &app.generated_colors,
+            &markers,
             app.current_swatch_index,
+            app.selected_color_index,
         );
+        if let Some((_, to)) = canvas_state.drop_target() {
+            draw_drop_indicator(&app.viewport, &app.layout, &app.generated_colors, to);
+        }
 
         // Track if egui wants input
         let mut egui_wants_pointer = false;
@@ -45,14 +130,71 @@ async fn main() {
         egui_macroquad::ui(|egui_ctx| {
             egui_wants_pointer = egui_ctx.wants_pointer_input();
 
+            // Shortcuts run before the windows so their requests are handled this frame
+            handle_shortcuts(egui_ctx, &keymap, &mut app, &mut swatch_editor_state, &mut open_windows);
+            draw_command_palette(
+                egui_ctx,
+                &mut app,
+                &keymap,
+                &mut command_palette_state,
+                &mut swatch_editor_state,
+                &mut export_state,
+                &mut open_windows,
+            );
+
             // Draw all UI windows
-            draw_top_panel(egui_ctx, &mut app);
+            draw_top_panel(egui_ctx, &mut app, &mut open_windows, &mut settings, autosave.error.as_deref());
+            let mut replaced = draw_recovery_prompt(egui_ctx, &mut app, &mut recovered);
+            replaced |= draw_file_dialogs(
+                egui_ctx,
+                &mut app,
+                &mut file_dialog_state,
+                &mut settings,
+                &mut open_windows.file_request,
+            );
+            if replaced {
+                swatch_editor_state.sync_with_swatch(&app);
+            }
             draw_swatch_editor(egui_ctx, &mut app, &mut swatch_editor_state);
             draw_palette_editor(egui_ctx, &mut app, &mut palette_editor_state);
+            draw_swatch_fit(egui_ctx, &mut app, &mut swatch_fit_state, &mut open_windows.swatch_fit);
+            draw_image_extract(
+                egui_ctx,
+                &mut app,
+                &mut image_extract_state,
+                &mut open_windows.image_extract,
+            );
+            draw_palette_preview(
+                egui_ctx,
+                &app,
+                &mut palette_preview_state,
+                &mut open_windows.palette_preview,
+            );
+            draw_gradient_map(
+                egui_ctx,
+                &app,
+                &mut gradient_map_state,
+                &mut open_windows.gradient_map,
+            );
+            draw_export_window(egui_ctx, &app, &mut export_state, &mut open_windows.export);
+            draw_lut_export(egui_ctx, &app, &mut lut_export_state, &mut open_windows.lut_export);
+            draw_shortcuts_window(egui_ctx, &mut keymap, &mut open_windows.shortcuts);
+            draw_background_compare(egui_ctx, &app, &mut settings, &mut open_windows.background_compare);
+            draw_canvas_context_menu(egui_ctx, &mut app, &mut canvas_state, &mut swatch_editor_state);
+            settings.save_pending(egui_ctx.input(|input| input.pointer.any_down()));
+
+            app.viewport.visible_area = Some(visible_canvas_area(egui_ctx));
         });
 
+        // Clicks on swatches select and drag them; elsewhere they pan the viewport
+        let canvas_captured =
+            handle_canvas_input(&mut app, &mut canvas_state, &mut swatch_editor_state, egui_wants_pointer);
+
         // Handle viewport input (only if egui doesn't want it)
-        app.viewport.handle_input(egui_wants_pointer);
+        app.viewport.handle_input(egui_wants_pointer || canvas_captured);
+        app.viewport.update(get_frame_time());
+
+        autosave.update(&app, &settings, get_time());
 
         egui_macroquad::draw();
         next_frame().await;
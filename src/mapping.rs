@@ -0,0 +1,206 @@
+use egui_macroquad::egui::Color32;
+
+use crate::color::{color_coordinates, coordinate_distance, ColorSpace};
+use crate::image_io::SourceImage;
+
+// =============================================================================
+// Palette mapping: remap an image to the nearest palette colors
+// =============================================================================
+//
+// Every pixel is replaced by the closest palette color, measured in a chosen
+// color space. Dithering trades spatial resolution for apparent color depth:
+// ordered dithering nudges each pixel by a fixed threshold pattern before
+// matching, error diffusion pushes each pixel's matching error onto the
+// pixels that haven't been processed yet.
+
+/// Dithering applied while remapping an image
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Dither {
+    #[default]
+    None,
+    Bayer4,
+    Bayer8,
+    FloydSteinberg,
+    Atkinson,
+}
+
+impl Dither {
+    pub const ALL: &'static [Dither] = &[
+        Dither::None,
+        Dither::Bayer4,
+        Dither::Bayer8,
+        Dither::FloydSteinberg,
+        Dither::Atkinson,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dither::None => "None",
+            Dither::Bayer4 => "Ordered (Bayer 4×4)",
+            Dither::Bayer8 => "Ordered (Bayer 8×8)",
+            Dither::FloydSteinberg => "Floyd–Steinberg",
+            Dither::Atkinson => "Atkinson",
+        }
+    }
+}
+
+/// Error diffusion kernels: (dx, dy, weight)
+const FLOYD_STEINBERG: &[(isize, isize, f32)] = &[
+    (1, 0, 7.0 / 16.0),
+    (-1, 1, 3.0 / 16.0),
+    (0, 1, 5.0 / 16.0),
+    (1, 1, 1.0 / 16.0),
+];
+/// Atkinson only diffuses 6/8 of the error, which keeps highlights and shadows clean
+const ATKINSON: &[(isize, isize, f32)] = &[
+    (1, 0, 1.0 / 8.0),
+    (2, 0, 1.0 / 8.0),
+    (-1, 1, 1.0 / 8.0),
+    (0, 1, 1.0 / 8.0),
+    (1, 1, 1.0 / 8.0),
+    (0, 2, 1.0 / 8.0),
+];
+
+/// Finds the nearest palette color, with palette coordinates precomputed
+pub struct PaletteMatcher {
+    space: ColorSpace,
+    colors: Vec<Color32>,
+    coordinates: Vec<[f32; 3]>,
+}
+
+impl PaletteMatcher {
+    pub fn new(colors: &[Color32], space: ColorSpace) -> Self {
+        Self {
+            space,
+            colors: colors.to_vec(),
+            coordinates: colors.iter().map(|&c| color_coordinates(c, space)).collect(),
+        }
+    }
+
+    /// The palette color closest to `color` (alpha is ignored)
+    pub fn nearest(&self, color: Color32) -> Color32 {
+        let p = color_coordinates(color, self.space);
+        self.coordinates
+            .iter()
+            .map(|&q| coordinate_distance(p, q, self.space))
+            .enumerate()
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map_or(color, |(index, _)| self.colors[index])
+    }
+}
+
+/// Remap every pixel of `source` to the palette. `strength` in [0.0, 1.0]
+/// scales the dithering. Alpha is kept from the source; fully transparent
+/// pixels are left untouched.
+pub fn remap_image(
+    source: &SourceImage,
+    palette: &[Color32],
+    space: ColorSpace,
+    dither: Dither,
+    strength: f32,
+) -> SourceImage {
+    if palette.is_empty() {
+        return source.clone();
+    }
+
+    let matcher = PaletteMatcher::new(palette, space);
+    let pixels = match dither {
+        Dither::None => source.pixels.iter().map(|&p| remap_pixel(&matcher, p, [0.0; 3])).collect(),
+        Dither::Bayer4 => ordered_dither(source, &matcher, 2, strength),
+        Dither::Bayer8 => ordered_dither(source, &matcher, 3, strength),
+        Dither::FloydSteinberg => diffuse_error(source, &matcher, FLOYD_STEINBERG, strength),
+        Dither::Atkinson => diffuse_error(source, &matcher, ATKINSON, strength),
+    };
+
+    SourceImage {
+        width: source.width,
+        height: source.height,
+        pixels,
+    }
+}
+
+/// Offset a pixel's RGB channels, then replace it with the nearest palette color
+fn remap_pixel(matcher: &PaletteMatcher, pixel: Color32, offset: [f32; 3]) -> Color32 {
+    if pixel.a() == 0 {
+        return pixel;
+    }
+    let shifted = shift_rgb(pixel, offset);
+    let nearest = matcher.nearest(shifted);
+    let [r, g, b, _] = nearest.to_srgba_unmultiplied();
+    Color32::from_rgba_unmultiplied(r, g, b, pixel.a())
+}
+
+fn shift_rgb(pixel: Color32, offset: [f32; 3]) -> Color32 {
+    let channel = |value: u8, delta: f32| (value as f32 + delta).round().clamp(0.0, 255.0) as u8;
+    let [r, g, b, _] = pixel.to_srgba_unmultiplied();
+    Color32::from_rgb(channel(r, offset[0]), channel(g, offset[1]), channel(b, offset[2]))
+}
+
+/// Threshold of a 2^bits × 2^bits Bayer matrix at (x, y), in [-0.5, 0.5)
+fn bayer_threshold(x: usize, y: usize, bits: u32) -> f32 {
+    let mut value = 0;
+    for bit in 0..bits {
+        let shift = 2 * (bits - 1 - bit);
+        value |= (((x ^ y) >> bit) & 1) << (shift + 1);
+        value |= ((y >> bit) & 1) << shift;
+    }
+    let cells = 1usize << (2 * bits);
+    (value as f32 + 0.5) / cells as f32 - 0.5
+}
+
+fn ordered_dither(source: &SourceImage, matcher: &PaletteMatcher, bits: u32, strength: f32) -> Vec<Color32> {
+    // Roughly the distance between neighbouring palette colors if they were
+    // spread evenly over the RGB cube
+    let spread = 255.0 / (matcher.colors.len() as f32).cbrt();
+    source
+        .pixels
+        .iter()
+        .enumerate()
+        .map(|(i, &pixel)| {
+            let (x, y) = (i % source.width, i / source.width);
+            let offset = bayer_threshold(x, y, bits) * spread * strength;
+            remap_pixel(matcher, pixel, [offset; 3])
+        })
+        .collect()
+}
+
+fn diffuse_error(
+    source: &SourceImage,
+    matcher: &PaletteMatcher,
+    kernel: &[(isize, isize, f32)],
+    strength: f32,
+) -> Vec<Color32> {
+    let (width, height) = (source.width, source.height);
+    // Accumulated error per pixel, in 0..255 units
+    let mut error = vec![[0.0f32; 3]; source.pixels.len()];
+    let mut output = Vec::with_capacity(source.pixels.len());
+
+    for (i, &pixel) in source.pixels.iter().enumerate() {
+        let mapped = remap_pixel(matcher, pixel, error[i]);
+        output.push(mapped);
+        if pixel.a() == 0 {
+            continue;
+        }
+
+        let wanted = shift_rgb(pixel, error[i]);
+        let residual = [
+            (wanted.r() as f32 - mapped.r() as f32) * strength,
+            (wanted.g() as f32 - mapped.g() as f32) * strength,
+            (wanted.b() as f32 - mapped.b() as f32) * strength,
+        ];
+
+        let (x, y) = ((i % width) as isize, (i / width) as isize);
+        for &(dx, dy, weight) in kernel {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || nx >= width as isize || ny >= height as isize {
+                continue;
+            }
+            let target = &mut error[ny as usize * width + nx as usize];
+            for c in 0..3 {
+                target[c] += residual[c] * weight;
+            }
+        }
+    }
+
+    output
+}
@@ -1,6 +1,11 @@
+use std::borrow::Cow;
+use std::fmt;
+
 use egui_macroquad::egui::Color32;
-use crate::color::{ColorSpace, lerp_color, extrapolate_color};
+use serde::{Deserialize, Serialize};
+use crate::color::{ColorSpace, delta_e, extrapolate_srgba, lerp_srgba, Srgba};
 use crate::curves::{Curve, CurveType};
+use crate::gamut::{from_output, in_gamut, to_output, GamutMapping, OutputSpace};
 
 // =============================================================================
 // ControlPoint: A color at a specific position in the swatch
@@ -11,23 +16,68 @@ use crate::curves::{Curve, CurveType};
 // should appear. Position 0 = leftmost (brightest), Position 1 = rightmost (darkest).
 // This allows control points at arbitrary positions, not just at discrete swatch indices.
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ControlPoint {
     /// Unique identifier for this control point (stable across reordering)
     pub id: u32,
     /// Normalized position in [0.0, 1.0] where this color appears
     /// 0.0 = left/bright, 1.0 = right/dark
     pub position: f32,
-    /// The color at this control point
-    pub color: Color32,
+    /// The color at this control point. For linked points this is kept in
+    /// sync with the link source by `Palette::resolve_links`.
+    pub color: Srgba,
+    /// Where this point takes its color from, if it is shared
+    pub link: Option<ColorLink>,
 }
 
 impl ControlPoint {
-    pub fn new(id: u32, position: f32, color: Color32) -> Self {
+    pub fn new(id: u32, position: f32, color: impl Into<Srgba>) -> Self {
         Self {
             id,
             position: position.clamp(0.0, 1.0),
-            color,
+            color: color.into(),
+            link: None,
+        }
+    }
+}
+
+// =============================================================================
+// ColorLink: Shared colors between swatches
+// =============================================================================
+//
+// A linked control point doesn't own its color: it follows a control point of
+// another swatch, or a palette-level named color. Links may chain (A follows
+// B, which follows a named color), but never loop back on themselves.
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ColorLink {
+    /// Follow a control point of a swatch, by stable IDs
+    ControlPoint { swatch_id: u32, point_id: u32 },
+    /// Follow a palette-level named color
+    Named(String),
+}
+
+/// A palette-level color that control points can link to
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NamedColor {
+    pub name: String,
+    pub color: Srgba,
+}
+
+/// Reasons a link can't be created
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinkError {
+    /// The link target doesn't exist
+    MissingTarget,
+    /// Following the link would eventually lead back to the linked point
+    Cycle,
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkError::MissingTarget => write!(f, "The linked color no longer exists"),
+            LinkError::Cycle => write!(f, "Linking here would create a cycle"),
         }
     }
 }
@@ -48,14 +98,30 @@ impl ControlPoint {
 // - Single control point: extrapolate using the curve (lighter before, darker after)
 // - Control points not at edges: extrapolate beyond them
 
-#[derive(Clone, Debug)]
+/// Range of step counts the editor offers for a swatch
+pub const MIN_SWATCH_SIZE: usize = 2;
+pub const MAX_SWATCH_SIZE: usize = 32;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Swatch {
+    /// Stable identifier, assigned by the palette (used by links)
+    id: u32,
+    /// Display and token name (e.g. "primary"); may be empty
+    pub name: String,
+    /// Extra names for individual steps (e.g. "surface" for step 1)
+    pub step_aliases: Vec<StepAlias>,
+    /// Steps held at a fixed color while the rest of the ramp changes
+    pub step_locks: Vec<StepLock>,
     /// Number of colors to generate in this swatch
     pub size: usize,
     /// Control points defining the gradient (sorted by position)
     control_points: Vec<ControlPoint>,
     /// The curve used for interpolation between control points
     pub interpolation_curve: CurveType,
+    /// The curve alpha follows between control points, separately from the
+    /// colors (e.g. a flame that keeps its hue while it fades out late)
+    pub alpha_curve: CurveType,
     /// The color space to use for interpolation
     pub color_space: ColorSpace,
     /// Counter for generating unique control point IDs
@@ -65,6 +131,10 @@ pub struct Swatch {
 impl Default for Swatch {
     fn default() -> Self {
         Self {
+            id: 0,
+            name: String::new(),
+            step_aliases: Vec::new(),
+            step_locks: Vec::new(),
             size: 8,
             control_points: vec![
                 // Bright color at position 0 (left)
@@ -73,6 +143,7 @@ impl Default for Swatch {
                 ControlPoint::new(1, 1.0, Color32::from_rgb(20, 20, 40)),
             ],
             interpolation_curve: CurveType::default(),
+            alpha_curve: CurveType::default(),
             color_space: ColorSpace::default(),
             next_control_point_id: 2, // Start after the two default points
         }
@@ -84,9 +155,14 @@ impl Swatch {
         // Find the max ID in the provided control points to set next_id correctly
         let max_id = control_points.iter().map(|cp| cp.id).max().unwrap_or(0);
         let mut swatch = Self {
+            id: 0,
+            name: String::new(),
+            step_aliases: Vec::new(),
+            step_locks: Vec::new(),
             size,
             control_points,
             interpolation_curve: curve,
+            alpha_curve: CurveType::default(),
             color_space,
             next_control_point_id: max_id + 1,
         };
@@ -94,6 +170,27 @@ impl Swatch {
         swatch
     }
 
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Whether the swatch could have been made in the editor: a size in its
+    /// range and control points inside the gradient. Data from outside, like
+    /// the clipboard, is checked with this before it's used.
+    pub fn is_valid(&self) -> bool {
+        (MIN_SWATCH_SIZE..=MAX_SWATCH_SIZE).contains(&self.size)
+            && self.control_points.iter().all(|cp| (0.0..=1.0).contains(&cp.position))
+    }
+
+    /// The swatch name, or a numbered fallback for unnamed swatches
+    pub fn display_name(&self, index: usize) -> String {
+        if self.name.trim().is_empty() {
+            format!("swatch-{}", index + 1)
+        } else {
+            self.name.trim().to_string()
+        }
+    }
+
     /// Ensures control points are sorted by position (required for interpolation)
     fn sort_control_points(&mut self) {
         self.control_points
@@ -107,40 +204,94 @@ impl Swatch {
     /// - Single control point: extrapolate darker before, lighter after
     /// - Multiple control points: piecewise interpolation with extrapolation at edges
     pub fn generate_colors(&self) -> Vec<Color32> {
-        if self.control_points.is_empty() {
-            return vec![Color32::BLACK; self.size];
+        self.generate_precise_colors().into_iter().map(Color32::from).collect()
+    }
+
+    /// The generated colors before quantization to 8 bits
+    pub fn generate_precise_colors(&self) -> Vec<Srgba> {
+        let points = self.anchor_points();
+        if points.is_empty() {
+            return vec![Srgba::BLACK; self.size];
         }
 
         let mut colors = Vec::with_capacity(self.size);
 
         for i in 0..self.size {
-            // Normalized position in [0.0, 1.0]
-            let t = if self.size > 1 {
-                i as f32 / (self.size - 1) as f32
-            } else {
-                0.5 // Single slot: use middle position
-            };
-
-            let color = self.sample_at(t);
+            let color = self.sample_points(&points, self.step_position(i));
             colors.push(color);
         }
 
+        // Locked steps keep their color exactly, whatever the interpolation
+        for lock in &self.step_locks {
+            if let Some(color) = colors.get_mut(lock.step) {
+                *color = lock.color;
+            }
+        }
+
         colors
     }
 
+    /// Normalized position of a generated step in [0.0, 1.0]
+    pub fn step_position(&self, step: usize) -> f32 {
+        if self.size > 1 {
+            step as f32 / (self.size - 1) as f32
+        } else {
+            0.5 // Single slot: use middle position
+        }
+    }
+
+    /// The points the ramp is interpolated through: the control points, plus
+    /// a point at every locked step. A lock replaces a control point on its step.
+    fn anchor_points(&self) -> Cow<'_, [ControlPoint]> {
+        let locks: Vec<&StepLock> = self.step_locks.iter().filter(|lock| lock.step < self.size).collect();
+        if locks.is_empty() {
+            return Cow::Borrowed(&self.control_points);
+        }
+
+        let half_step = 0.5 / self.size as f32;
+        let mut points: Vec<ControlPoint> = self
+            .control_points
+            .iter()
+            .filter(|cp| {
+                !locks
+                    .iter()
+                    .any(|lock| (cp.position - self.step_position(lock.step)).abs() < half_step)
+            })
+            .cloned()
+            .collect();
+        points.extend(locks.iter().map(|lock| ControlPoint {
+            id: u32::MAX,
+            position: self.step_position(lock.step),
+            color: lock.color,
+            link: None,
+        }));
+        points.sort_by(|a, b| a.position.total_cmp(&b.position));
+        Cow::Owned(points)
+    }
+
     /// Sample the gradient at a normalized position t in [0.0, 1.0]
-    fn sample_at(&self, t: f32) -> Color32 {
-        if self.control_points.is_empty() {
-            return Color32::BLACK;
+    pub fn sample_at(&self, t: f32) -> Color32 {
+        self.sample_precise(t).into()
+    }
+
+    /// Sample the gradient without quantizing the color
+    pub fn sample_precise(&self, t: f32) -> Srgba {
+        self.sample_points(&self.anchor_points(), t)
+    }
+
+    /// Sample the gradient through `points`, sorted by position
+    fn sample_points(&self, points: &[ControlPoint], t: f32) -> Srgba {
+        if points.is_empty() {
+            return Srgba::BLACK;
         }
 
         // Single control point: extrapolate based on distance from it
-        if self.control_points.len() == 1 {
-            return self.sample_single_point(t);
+        if points.len() == 1 {
+            return self.sample_single_point(&points[0], t);
         }
 
-        let first = &self.control_points[0];
-        let last = self.control_points.last().unwrap();
+        let first = &points[0];
+        let last = points.last().unwrap();
 
         // Before first control point: extrapolate lighter (toward position 0 = bright)
         if t < first.position {
@@ -153,32 +304,30 @@ impl Swatch {
         }
 
         // Between control points: interpolate
-        self.interpolate_between(t)
+        self.interpolate_between(points, t)
     }
 
     /// Handle single control point case: extrapolate in both directions
     /// Position 0 = bright (left), Position 1 = dark (right)
-    fn sample_single_point(&self, t: f32) -> Color32 {
-        let cp = &self.control_points[0];
-        
+    fn sample_single_point(&self, cp: &ControlPoint, t: f32) -> Srgba {
         if t < cp.position {
             // Before the control point: go lighter (toward bright/left)
             let distance = cp.position - t;
             // Apply curve to the distance for non-linear extrapolation
             let curved_distance = self.interpolation_curve.sample(distance.min(1.0));
-            extrapolate_color(cp.color, curved_distance, self.color_space) // positive = lighter
+            extrapolate_srgba(cp.color, curved_distance, self.color_space) // positive = lighter
         } else if t > cp.position {
             // After the control point: go darker (toward dark/right)
             let distance = t - cp.position;
             let curved_distance = self.interpolation_curve.sample(distance.min(1.0));
-            extrapolate_color(cp.color, -curved_distance, self.color_space) // negative = darker
+            extrapolate_srgba(cp.color, -curved_distance, self.color_space) // negative = darker
         } else {
             cp.color
         }
     }
 
     /// Extrapolate before the first control point (toward lighter/brighter)
-    fn extrapolate_before(&self, t: f32, first: &ControlPoint) -> Color32 {
+    fn extrapolate_before(&self, t: f32, first: &ControlPoint) -> Srgba {
         // How far before the first point (normalized to the "before" region)
         let region_size = first.position;
         if region_size <= 0.0 {
@@ -190,11 +339,11 @@ impl Swatch {
         let curved_distance = self.interpolation_curve.sample(normalized_distance);
         
         // Positive = lighter (going toward position 0 = bright)
-        extrapolate_color(first.color, curved_distance, self.color_space)
+        extrapolate_srgba(first.color, curved_distance, self.color_space)
     }
 
     /// Extrapolate after the last control point (toward darker)
-    fn extrapolate_after(&self, t: f32, last: &ControlPoint) -> Color32 {
+    fn extrapolate_after(&self, t: f32, last: &ControlPoint) -> Srgba {
         let region_size = 1.0 - last.position;
         if region_size <= 0.0 {
             return last.color;
@@ -204,13 +353,13 @@ impl Swatch {
         let curved_distance = self.interpolation_curve.sample(normalized_distance);
         
         // Negative = darker (going toward position 1 = dark)
-        extrapolate_color(last.color, -curved_distance, self.color_space)
+        extrapolate_srgba(last.color, -curved_distance, self.color_space)
     }
 
     /// Interpolate between control points (t is within the control point range)
-    fn interpolate_between(&self, t: f32) -> Color32 {
+    fn interpolate_between(&self, points: &[ControlPoint], t: f32) -> Srgba {
         // Find the two control points that bracket position t
-        let (cp_before, cp_after) = self.find_bracketing_points(t);
+        let (cp_before, cp_after) = find_bracketing_points(points, t);
 
         // Calculate local_t: how far between cp_before and cp_after we are
         let segment_length = cp_after.position - cp_before.position;
@@ -222,32 +371,26 @@ impl Swatch {
 
         // Apply the interpolation curve to get the curved interpolation factor
         let curved_t = self.interpolation_curve.sample(local_t);
+        let alpha_t = self.alpha_curve.sample(local_t);
 
         // Lerp between the two colors in the selected color space
-        lerp_color(cp_before.color, cp_after.color, curved_t, self.color_space)
-    }
-
-    /// Find the two control points that bracket position t.
-    /// Returns (before, after) where before.position <= t <= after.position
-    fn find_bracketing_points(&self, t: f32) -> (&ControlPoint, &ControlPoint) {
-        // Find the segment containing t
-        for i in 0..self.control_points.len() - 1 {
-            let current = &self.control_points[i];
-            let next = &self.control_points[i + 1];
-            if t >= current.position && t <= next.position {
-                return (current, next);
-            }
-        }
-
-        // Fallback (shouldn't happen if t is within range)
-        let last = self.control_points.last().unwrap();
-        (last, last)
+        lerp_srgba(cp_before.color, cp_after.color, curved_t, alpha_t, self.color_space)
     }
 
     // =========================================================================
     // Control point management
     // =========================================================================
 
+    /// Drop links to control points of other swatches, which don't carry over
+    /// when the swatch is pasted into another palette. Links to named colors stay.
+    pub fn unlink_control_points(&mut self) {
+        for cp in &mut self.control_points {
+            if matches!(cp.link, Some(ColorLink::ControlPoint { .. })) {
+                cp.link = None;
+            }
+        }
+    }
+
     pub fn control_points(&self) -> &[ControlPoint] {
         &self.control_points
     }
@@ -256,7 +399,7 @@ impl Swatch {
         &mut self.control_points
     }
 
-    pub fn add_control_point(&mut self, position: f32, color: Color32) {
+    pub fn add_control_point(&mut self, position: f32, color: impl Into<Srgba>) {
         let id = self.next_control_point_id;
         self.next_control_point_id += 1;
         self.control_points.push(ControlPoint::new(id, position, color));
@@ -275,16 +418,19 @@ impl Swatch {
         self.control_points.retain(|cp| cp.id != id);
     }
 
-    pub fn set_control_point_color(&mut self, index: usize, color: Color32) {
+    /// Set a control point's color. An explicit color replaces any link.
+    pub fn set_control_point_color(&mut self, index: usize, color: impl Into<Srgba>) {
         if let Some(cp) = self.control_points.get_mut(index) {
-            cp.color = color;
+            cp.color = color.into();
+            cp.link = None;
         }
     }
 
-    /// Set control point color by its stable ID
-    pub fn set_control_point_color_by_id(&mut self, id: u32, color: Color32) {
+    /// Set control point color by its stable ID. An explicit color replaces any link.
+    pub fn set_control_point_color_by_id(&mut self, id: u32, color: impl Into<Srgba>) {
         if let Some(cp) = self.control_points.iter_mut().find(|cp| cp.id == id) {
-            cp.color = color;
+            cp.color = color.into();
+            cp.link = None;
         }
     }
 
@@ -333,26 +479,601 @@ impl Swatch {
             .iter()
             .position(|cp| (cp.position - position).abs() <= tolerance)
     }
+
+    // =========================================================================
+    // Step locks
+    // =========================================================================
+
+    pub fn is_step_locked(&self, step: usize) -> bool {
+        self.step_locks.iter().any(|lock| lock.step == step)
+    }
+
+    /// Lock a step at the color it has now
+    pub fn lock_step(&mut self, step: usize) {
+        if step >= self.size || self.is_step_locked(step) {
+            return;
+        }
+        let color = self.generate_precise_colors()[step];
+        self.lock_step_at(step, color);
+    }
+
+    /// Lock a step at `color`, replacing the color of an existing lock
+    pub fn lock_step_at(&mut self, step: usize, color: impl Into<Srgba>) {
+        if step >= self.size {
+            return;
+        }
+        self.unlock_step(step);
+        self.step_locks.push(StepLock { step, color: color.into() });
+        self.step_locks.sort_by_key(|lock| lock.step);
+    }
+
+    pub fn unlock_step(&mut self, step: usize) {
+        self.step_locks.retain(|lock| lock.step != step);
+    }
+
+    /// Change the number of steps, keeping every control point where it is.
+    /// Locks on steps that no longer exist are dropped, so they don't come
+    /// back on whatever color lands there when the swatch grows again.
+    pub fn set_size(&mut self, size: usize) {
+        self.size = size;
+        self.step_locks.retain(|lock| lock.step < size);
+    }
+
+    // =========================================================================
+    // Pinning and resizing
+    // =========================================================================
+
+    /// Set the color of a step by adding a control point on it, or
+    /// recoloring the control point already there
+    pub fn pin_step(&mut self, step: usize, color: impl Into<Srgba>) {
+        let position = self.step_position(step);
+        let tolerance = 0.5 / self.size.max(1) as f32;
+        match self.has_control_point_at(position, tolerance) {
+            Some(index) => self.set_control_point_color(index, color),
+            None => self.add_control_point(position, color),
+        }
+    }
+
+    /// Remove the control point on a step. Returns false if there was none.
+    pub fn unpin_step(&mut self, step: usize) -> bool {
+        let tolerance = 0.5 / self.size.max(1) as f32;
+        let Some(index) = self.has_control_point_at(self.step_position(step), tolerance) else {
+            return false;
+        };
+        self.remove_control_point(index);
+        true
+    }
+
+    /// Change the number of steps. Control points and locks on a step move
+    /// with it, so pinned colors stay on their step; control points between
+    /// steps keep their place relative to the steps around them.
+    ///
+    /// Resampling down can land two pinned or locked steps on one new step,
+    /// where only the first is kept. Returns the old steps that were dropped
+    /// that way.
+    pub fn resize(&mut self, resize: StepResize) -> Vec<usize> {
+        let old_size = self.size;
+        let Some(new_size) = resize.new_size(old_size) else {
+            return Vec::new();
+        };
+        let mut dropped = Vec::new();
+        let new_position = |step: f32| {
+            if new_size > 1 {
+                (step / (new_size - 1) as f32).clamp(0.0, 1.0)
+            } else {
+                0.5
+            }
+        };
+
+        let mut moved = Vec::with_capacity(self.control_points.len());
+        for mut cp in std::mem::take(&mut self.control_points) {
+            let old_step = self.step_at(cp.position);
+            match old_step {
+                Some(step) => match resize.map_step(step, old_size) {
+                    Some(new_step) => cp.position = new_position(new_step as f32),
+                    None => continue,
+                },
+                None if old_size > 1 => {
+                    let step = cp.position * (old_size - 1) as f32;
+                    cp.position = new_position(resize.map_coordinate(step, old_size));
+                }
+                None => {}
+            }
+            if moved.iter().all(|other: &ControlPoint| (other.position - cp.position).abs() > 1e-6) {
+                moved.push(cp);
+            } else {
+                dropped.extend(old_step);
+            }
+        }
+        self.control_points = moved;
+        self.sort_control_points();
+
+        let mut locks = Vec::with_capacity(self.step_locks.len());
+        for lock in &self.step_locks {
+            if let Some(step) = resize.map_step(lock.step, old_size) {
+                if locks.iter().all(|other: &StepLock| other.step != step) {
+                    locks.push(StepLock { step, ..*lock });
+                } else {
+                    dropped.push(lock.step);
+                }
+            }
+        }
+        self.step_locks = locks;
+
+        self.step_aliases = std::mem::take(&mut self.step_aliases)
+            .into_iter()
+            .filter_map(|alias| {
+                let step = resize.map_step(alias.step, old_size)?;
+                Some(StepAlias { step, ..alias })
+            })
+            .collect();
+
+        self.size = new_size;
+        dropped.sort_unstable();
+        dropped.dedup();
+        dropped
+    }
+
+    /// The step a position lies exactly on, if any
+    fn step_at(&self, position: f32) -> Option<usize> {
+        (0..self.size).find(|&step| (self.step_position(step) - position).abs() < STEP_POSITION_EPSILON)
+    }
+}
+
+/// Positions closer than this to a step count as on it
+const STEP_POSITION_EPSILON: f32 = 1e-4;
+
+/// A change to the number of steps of a swatch, for `Swatch::resize`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepResize {
+    /// Insert a new step at this index, halfway between its neighbours
+    Insert(usize),
+    /// Remove the step at this index
+    Remove(usize),
+    /// Spread the ramp over this many steps
+    Resample(usize),
+}
+
+impl StepResize {
+    /// Number of steps after resizing a swatch of `size`, or None if the
+    /// resize doesn't apply to it or would leave the editor's size range
+    pub fn new_size(&self, size: usize) -> Option<usize> {
+        let new_size = match *self {
+            StepResize::Insert(index) if index <= size => size + 1,
+            StepResize::Remove(index) if index < size => size - 1,
+            StepResize::Resample(new_size) if new_size != size => new_size,
+            _ => return None,
+        };
+        (MIN_SWATCH_SIZE..=MAX_SWATCH_SIZE).contains(&new_size).then_some(new_size)
+    }
+
+    /// New index of a step, or None if it was removed
+    pub fn map_step(&self, step: usize, size: usize) -> Option<usize> {
+        match *self {
+            StepResize::Insert(index) => Some(if step < index { step } else { step + 1 }),
+            StepResize::Remove(index) if step == index => None,
+            StepResize::Remove(index) => Some(if step < index { step } else { step - 1 }),
+            StepResize::Resample(new_size) => {
+                Some(self.map_coordinate(step as f32, size).round().clamp(0.0, (new_size - 1) as f32) as usize)
+            }
+        }
+    }
+
+    /// New fractional step coordinate of an old one, for positions between
+    /// steps. Steps map as in `map_step`; a removed step closes up halfway.
+    fn map_coordinate(&self, step: f32, size: usize) -> f32 {
+        match *self {
+            StepResize::Resample(new_size) if size > 1 => step * (new_size - 1) as f32 / (size - 1) as f32,
+            StepResize::Resample(_) => step,
+            StepResize::Insert(index) => {
+                let index = index as f32;
+                if step <= index - 1.0 {
+                    step
+                } else if step >= index {
+                    step + 1.0
+                } else {
+                    // The gap before the new step doubles
+                    index - 1.0 + (step - (index - 1.0)) * 2.0
+                }
+            }
+            StepResize::Remove(index) => {
+                let index = index as f32;
+                if step <= index - 1.0 {
+                    step
+                } else if step >= index + 1.0 {
+                    step - 1.0
+                } else {
+                    // The two gaps around the removed step become one
+                    index - 1.0 + (step - (index - 1.0)) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// Find the two control points that bracket position t.
+/// Returns (before, after) where before.position <= t <= after.position
+fn find_bracketing_points(points: &[ControlPoint], t: f32) -> (&ControlPoint, &ControlPoint) {
+    // Find the segment containing t
+    for pair in points.windows(2) {
+        if t >= pair[0].position && t <= pair[1].position {
+            return (&pair[0], &pair[1]);
+        }
+    }
+
+    // Fallback (shouldn't happen if t is within range)
+    let last = points.last().unwrap();
+    (last, last)
+}
+
+// =============================================================================
+// StepLock: generated steps held at a fixed color
+// =============================================================================
+//
+// A locked step keeps its color while control points, the curve or the color
+// space change. It acts as an extra control point on its step, so the rest of
+// the ramp is interpolated around it.
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct StepLock {
+    /// Index into the generated colors
+    pub step: usize,
+    pub color: Srgba,
+}
+
+// =============================================================================
+// Step naming: names for the individual colors of a swatch
+// =============================================================================
+
+/// An additional, semantic name for one step of a swatch
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StepAlias {
+    /// Index into the generated colors
+    pub step: usize,
+    pub name: String,
+}
+
+/// How the steps of every swatch are named in exports (e.g. `primary-500`)
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub enum StepNaming {
+    /// 1, 2, 3, ...
+    #[default]
+    Index,
+    /// 50, 100, ..., 900, 950 spread over the swatch, like Tailwind
+    Tailwind,
+    /// 100, 200, 300, ...
+    Hundreds,
+    /// User-provided names; steps without one fall back to their index
+    Custom(Vec<String>),
+}
+
+/// Tailwind's standard 11-step scale
+const TAILWIND_STEPS: &[u32] = &[50, 100, 200, 300, 400, 500, 600, 700, 800, 900, 950];
+
+impl StepNaming {
+    /// Names for each scheme, without the parameters of `Custom`
+    pub const KINDS: &'static [(&'static str, StepNaming)] = &[
+        ("Index (1, 2, 3...)", StepNaming::Index),
+        ("Tailwind (50..950)", StepNaming::Tailwind),
+        ("Hundreds (100, 200...)", StepNaming::Hundreds),
+        ("Custom", StepNaming::Custom(Vec::new())),
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StepNaming::Index => Self::KINDS[0].0,
+            StepNaming::Tailwind => Self::KINDS[1].0,
+            StepNaming::Hundreds => Self::KINDS[2].0,
+            StepNaming::Custom(_) => Self::KINDS[3].0,
+        }
+    }
+
+    /// Whether two schemes are the same kind, ignoring custom names
+    pub fn same_kind(&self, other: &StepNaming) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// Names for the steps of a swatch with `size` colors
+    pub fn step_names(&self, size: usize) -> Vec<String> {
+        (0..size).map(|step| self.step_name(step, size)).collect()
+    }
+
+    fn step_name(&self, step: usize, size: usize) -> String {
+        match self {
+            StepNaming::Index => (step + 1).to_string(),
+            StepNaming::Hundreds => ((step + 1) * 100).to_string(),
+            StepNaming::Tailwind => {
+                if size == TAILWIND_STEPS.len() {
+                    return TAILWIND_STEPS[step].to_string();
+                }
+                if size <= 1 {
+                    return "500".to_string();
+                }
+                // Spread 50..950 evenly, rounded as coarsely as still keeps names unique
+                let spacing = 900.0 / (size - 1) as f32;
+                let granularity = if spacing >= 50.0 {
+                    50.0
+                } else if spacing >= 10.0 {
+                    10.0
+                } else {
+                    1.0
+                };
+                let value = 50.0 + step as f32 * spacing;
+                (((value / granularity).round() * granularity) as u32).to_string()
+            }
+            StepNaming::Custom(names) => names
+                .get(step)
+                .map(|name| name.trim())
+                .filter(|name| !name.is_empty())
+                .map_or_else(|| (step + 1).to_string(), str::to_string),
+        }
+    }
 }
 
 // =============================================================================
 // Palette: A collection of swatches
 // =============================================================================
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Palette {
     pub swatches: Vec<Swatch>,
+    /// Colors shared by name between swatches
+    pub named_colors: Vec<NamedColor>,
+    /// How swatch steps are named in token exports
+    pub step_naming: StepNaming,
+    /// Maximum number of distinct colors across all swatches (None = unlimited)
+    pub color_budget: Option<usize>,
+    /// Colors closer than this ΔE (OkLab) count as duplicates; 0 merges only identical colors
+    pub merge_threshold: f32,
+    /// Color space generated colors are mapped into and exported in
+    pub output_space: OutputSpace,
+    /// How generated colors outside the output gamut are brought into it
+    pub gamut_mapping: GamutMapping,
+    /// Counter for generating unique swatch IDs
+    next_swatch_id: u32,
 }
 
 impl Palette {
     pub fn new() -> Self {
-        Self {
-            swatches: vec![Swatch::default()],
+        let mut palette = Self::default();
+        palette.add_swatch(Swatch::default());
+        palette
+    }
+
+    /// Append a swatch, giving it a fresh ID
+    pub fn add_swatch(&mut self, swatch: Swatch) {
+        self.insert_swatch(self.swatches.len(), swatch);
+    }
+
+    /// Insert a swatch at `index`, giving it a fresh ID
+    pub fn insert_swatch(&mut self, index: usize, mut swatch: Swatch) {
+        swatch.id = self.next_swatch_id;
+        self.next_swatch_id += 1;
+        self.swatches.insert(index, swatch);
+    }
+
+    /// Whether every swatch is valid (see `Swatch::is_valid`)
+    pub fn is_valid(&self) -> bool {
+        self.swatches.iter().all(Swatch::is_valid)
+    }
+
+    /// Append the swatches and named colors of another palette. Links between
+    /// the appended swatches follow them to their new IDs; named colors that
+    /// already exist here keep their current value.
+    pub fn append_palette(&mut self, other: Palette) {
+        let id_map: Vec<(u32, u32)> = other
+            .swatches
+            .iter()
+            .enumerate()
+            .map(|(i, swatch)| (swatch.id, self.next_swatch_id + i as u32))
+            .collect();
+        for mut swatch in other.swatches {
+            for cp in &mut swatch.control_points {
+                if let Some(ColorLink::ControlPoint { swatch_id, .. }) = &mut cp.link {
+                    match id_map.iter().find(|(old, _)| old == swatch_id) {
+                        Some(&(_, new)) => *swatch_id = new,
+                        None => cp.link = None,
+                    }
+                }
+            }
+            self.add_swatch(swatch);
+        }
+        for named in other.named_colors {
+            if self.named_color(&named.name).is_none() {
+                self.named_colors.push(named);
+            }
         }
+    }
 
+    /// Generated colors of a swatch in the output space, as exports write them
+    pub fn output_colors(&self, index: usize) -> Vec<Srgba> {
+        self.swatch_output_colors(&self.swatches[index])
     }
 
-    pub fn add_swatch(&mut self, swatch: Swatch) {
-        self.swatches.push(swatch);
+    fn swatch_output_colors(&self, swatch: &Swatch) -> Vec<Srgba> {
+        swatch
+            .generate_precise_colors()
+            .into_iter()
+            .map(|color| self.to_output(color))
+            .collect()
+    }
+
+    /// Generated colors of a swatch as an sRGB display shows them: output
+    /// colors beyond sRGB are clipped
+    pub fn display_colors(&self, index: usize) -> Vec<Color32> {
+        self.swatch_display_colors(&self.swatches[index])
+    }
+
+    /// `display_colors` for a swatch that isn't part of the palette, such as
+    /// a preview of an edit
+    pub fn swatch_display_colors(&self, swatch: &Swatch) -> Vec<Color32> {
+        self.swatch_output_colors(swatch)
+            .into_iter()
+            .map(|color| from_output(color, self.output_space).into())
+            .collect()
+    }
+
+    /// The gradient of a swatch at `t`, mapped to the output space and shown
+    /// in sRGB like `display_colors`
+    pub fn swatch_display_sample(&self, swatch: &Swatch, t: f32) -> Color32 {
+        from_output(self.to_output(swatch.sample_precise(t)), self.output_space).into()
+    }
+
+    /// For each step of a swatch, whether its output color lies outside sRGB
+    /// and so can't be shown exactly on the canvas
+    pub fn outside_srgb(&self, index: usize) -> Vec<bool> {
+        if self.output_space == OutputSpace::Srgb {
+            return vec![false; self.swatches[index].size];
+        }
+        self.output_colors(index)
+            .into_iter()
+            .map(|color| !in_gamut(from_output(color, self.output_space), OutputSpace::Srgb))
+            .collect()
+    }
+
+    /// Map an (extended sRGB) color into the output space
+    pub fn to_output(&self, color: Srgba) -> Srgba {
+        to_output(color, self.output_space, self.gamut_mapping)
+    }
+
+    pub fn find_swatch_index_by_id(&self, id: u32) -> Option<usize> {
+        self.swatches.iter().position(|s| s.id == id)
+    }
+
+    pub fn named_color(&self, name: &str) -> Option<&NamedColor> {
+        self.named_colors.iter().find(|c| c.name == name)
+    }
+
+    /// The first `color-N` no named color uses yet
+    pub fn unused_color_name(&self) -> String {
+        (1..)
+            .map(|n| format!("color-{n}"))
+            .find(|name| self.named_color(name).is_none())
+            .unwrap_or_default()
+    }
+
+    /// Rename a named color, updating every link that points to it. Empty
+    /// names and names another color has are rejected, returning false, since
+    /// links are resolved by name.
+    pub fn rename_named_color(&mut self, index: usize, new_name: &str) -> bool {
+        let new_name = new_name.trim().to_string();
+        let taken = self.named_colors.iter().enumerate().any(|(i, c)| i != index && c.name == new_name);
+        if new_name.is_empty() || taken {
+            return false;
+        }
+        let Some(named) = self.named_colors.get_mut(index) else { return false };
+        let old_name = std::mem::replace(&mut named.name, new_name.clone());
+        for cp in self.swatches.iter_mut().flat_map(|s| s.control_points.iter_mut()) {
+            if cp.link == Some(ColorLink::Named(old_name.clone())) {
+                cp.link = Some(ColorLink::Named(new_name.clone()));
+            }
+        }
+        true
+    }
+
+    // =========================================================================
+    // Links
+    // =========================================================================
+
+    fn control_point(&self, swatch_id: u32, point_id: u32) -> Option<&ControlPoint> {
+        let swatch = &self.swatches[self.find_swatch_index_by_id(swatch_id)?];
+        swatch.control_points.iter().find(|cp| cp.id == point_id)
+    }
+
+    /// Link (or with `None`, unlink) a control point. Links to missing colors
+    /// and links that would form a cycle are rejected.
+    pub fn set_link(&mut self, swatch_id: u32, point_id: u32, link: Option<ColorLink>) -> Result<(), LinkError> {
+        if let Some(link) = &link {
+            // Walk the chain starting at the new target; reaching the point
+            // being linked means the chain would loop
+            let mut next = Some(link.clone());
+            while let Some(current) = next {
+                next = match current {
+                    ColorLink::Named(name) => {
+                        self.named_color(&name).ok_or(LinkError::MissingTarget)?;
+                        None
+                    }
+                    ColorLink::ControlPoint { swatch_id: s, point_id: p } => {
+                        if (s, p) == (swatch_id, point_id) {
+                            return Err(LinkError::Cycle);
+                        }
+                        self.control_point(s, p).ok_or(LinkError::MissingTarget)?.link.clone()
+                    }
+                };
+            }
+        }
+
+        let swatch_index = self.find_swatch_index_by_id(swatch_id).ok_or(LinkError::MissingTarget)?;
+        let cp = self.swatches[swatch_index]
+            .control_points
+            .iter_mut()
+            .find(|cp| cp.id == point_id)
+            .ok_or(LinkError::MissingTarget)?;
+        cp.link = link;
+        Ok(())
+    }
+
+    /// The color a link currently resolves to, following chained links.
+    /// Returns None for broken links (missing target or a cycle).
+    pub fn resolve_link(&self, link: &ColorLink) -> Option<Srgba> {
+        let mut current = link.clone();
+        // A chain can't be longer than the number of control points without looping
+        let max_steps = self.swatches.iter().map(|s| s.control_points.len()).sum::<usize>() + 1;
+        for _ in 0..max_steps {
+            match current {
+                ColorLink::Named(name) => return self.named_color(&name).map(|c| c.color),
+                ColorLink::ControlPoint { swatch_id, point_id } => {
+                    let cp = self.control_point(swatch_id, point_id)?;
+                    match &cp.link {
+                        Some(next) => current = next.clone(),
+                        None => return Some(cp.color),
+                    }
+                }
+            }
+        }
+        None
     }
-}
\ No newline at end of file
+
+    /// Copy the source colors into every linked control point. Returns the
+    /// indices of the swatches whose control points changed, so only those
+    /// need to be regenerated. Broken links keep their last known color.
+    pub fn resolve_links(&mut self) -> Vec<usize> {
+        let mut updates = Vec::new();
+        for (swatch_index, swatch) in self.swatches.iter().enumerate() {
+            for (point_index, cp) in swatch.control_points.iter().enumerate() {
+                let Some(link) = &cp.link else { continue };
+                if let Some(color) = self.resolve_link(link) {
+                    if color != cp.color {
+                        updates.push((swatch_index, point_index, color));
+                    }
+                }
+            }
+        }
+
+        let mut changed: Vec<usize> = Vec::new();
+        for (swatch_index, point_index, color) in updates {
+            self.swatches[swatch_index].control_points[point_index].color = color;
+            if !changed.contains(&swatch_index) {
+                changed.push(swatch_index);
+            }
+        }
+        changed
+    }
+}
+
+/// Remove duplicate and near-duplicate colors, keeping the first occurrence
+/// of each. The order of the remaining colors is unchanged, so the result is
+/// stable as long as the input order is.
+pub fn deduplicate_colors(colors: &[Color32], threshold: f32) -> Vec<Color32> {
+    let mut unique: Vec<Color32> = Vec::with_capacity(colors.len());
+    for &color in colors {
+        let is_duplicate = unique
+            .iter()
+            .any(|&kept| kept == color || delta_e(kept, color) <= threshold);
+        if !is_duplicate {
+            unique.push(color);
+        }
+    }
+    unique
+}
@@ -0,0 +1,128 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use macroquad::prelude::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::app::App;
+use crate::palette::{Palette, Swatch};
+use crate::rendering::PaletteLayout;
+
+// =============================================================================
+// Project files: the editable state of the app, as JSON
+// =============================================================================
+//
+// A project holds the palette model (swatches, control points, names and
+// constraints) plus the selection and view. Generated colors are not stored;
+// they are regenerated on load.
+
+/// Written into every project; files from a newer version are rejected.
+/// Version 2 stores colors as float channels; version 1 files still load.
+pub const PROJECT_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize)]
+pub struct Project {
+    pub version: u32,
+    pub palette: Palette,
+    pub current_swatch_index: usize,
+    pub viewport_offset: [f32; 2],
+    pub viewport_zoom: f32,
+    /// Missing from older files, which get the default layout
+    #[serde(default)]
+    pub layout: PaletteLayout,
+}
+
+/// Why a project could not be read or written
+#[derive(Debug)]
+pub enum ProjectError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// Saved by a newer version of the app
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectError::Io(err) => write!(f, "{err}"),
+            ProjectError::Json(err) => write!(f, "not a valid project file ({err})"),
+            ProjectError::UnsupportedVersion(version) => {
+                write!(f, "made by a newer version (format {version}, this app reads {PROJECT_VERSION})")
+            }
+        }
+    }
+}
+
+impl From<io::Error> for ProjectError {
+    fn from(err: io::Error) -> Self {
+        ProjectError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for ProjectError {
+    fn from(err: serde_json::Error) -> Self {
+        ProjectError::Json(err)
+    }
+}
+
+impl Project {
+    pub fn from_app(app: &App) -> Self {
+        Self {
+            version: PROJECT_VERSION,
+            palette: app.palette.clone(),
+            current_swatch_index: app.current_swatch_index,
+            viewport_offset: app.viewport.offset.to_array(),
+            viewport_zoom: app.viewport.zoom,
+            layout: app.layout,
+        }
+    }
+
+    /// Replace the app's palette, selection and view with the project's.
+    /// The file path and modified state are left to the caller.
+    pub fn apply(self, app: &mut App) {
+        app.palette = self.palette;
+        if app.palette.swatches.is_empty() {
+            app.palette.add_swatch(Swatch::default());
+        }
+        app.current_swatch_index = self.current_swatch_index.min(app.palette.swatches.len() - 1);
+        app.viewport.set_view(Vec2::from_array(self.viewport_offset), self.viewport_zoom);
+        app.layout = self.layout;
+        app.regenerate_all_colors();
+    }
+
+    pub fn to_json(&self) -> Result<String, ProjectError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, ProjectError> {
+        // Check the version first: a newer format may not parse at all
+        #[derive(Deserialize)]
+        struct Header {
+            version: u32,
+        }
+        let header: Header = serde_json::from_str(text)?;
+        if header.version > PROJECT_VERSION {
+            return Err(ProjectError::UnsupportedVersion(header.version));
+        }
+        Ok(serde_json::from_str(text)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ProjectError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ProjectError> {
+        write_atomically(path.as_ref(), &self.to_json()?)?;
+        Ok(())
+    }
+}
+
+/// Write through a temporary file, so a crash mid-write never leaves a
+/// truncated file behind
+pub fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, path)
+}
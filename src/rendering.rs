@@ -1,6 +1,8 @@
 use egui_macroquad::egui::Color32;
 use macroquad::prelude::*;
 
+use serde::{Deserialize, Serialize};
+
 use crate::viewport::Viewport;
 
 // =============================================================================
@@ -56,7 +58,7 @@ pub fn draw_checker_background(viewport: &Viewport) {
 }
 
 // =============================================================================
-// Palette rendering in world space
+// Palette layout
 // =============================================================================
 
 /// Layout constants for auto-aligned swatches
@@ -64,53 +66,352 @@ const SWATCH_VERTICAL_SPACING: f32 = 8.0;
 const SWATCH_START_X: f32 = 0.0;
 const SWATCH_START_Y: f32 = 0.0;
 const INDEX_LABEL_OFFSET: f32 = 30.0; // Space for index label to the left
+const STEP_LABEL_HEIGHT: f32 = 24.0; // Space for step numbers below the squares
+const INDEX_LABEL_HEIGHT: f32 = 24.0; // Space for index label above a column
+const STEP_LABEL_WIDTH: f32 = 24.0; // Space for step numbers left of a column
+const GRID_SPACING: f32 = 16.0; // Gap between grid cells
+const PIXEL_SQUARE_SIZE: f32 = 16.0; // Squares in pixel mode have no gaps
+
+/// How swatches are arranged in the viewport
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LayoutMode {
+    /// One ramp per row, left-aligned
+    #[default]
+    Vertical,
+    /// One ramp per column, top-aligned
+    Horizontal,
+    /// Rows of ramps wrapped into a roughly square grid
+    Grid,
+    /// One ramp per row, shifted so the anchor steps line up
+    Centered,
+    /// Rows of small squares with no gaps or labels
+    Pixel,
+}
+
+impl LayoutMode {
+    pub const ALL: &'static [LayoutMode] = &[
+        LayoutMode::Vertical,
+        LayoutMode::Horizontal,
+        LayoutMode::Grid,
+        LayoutMode::Centered,
+        LayoutMode::Pixel,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LayoutMode::Vertical => "Vertical Stack",
+            LayoutMode::Horizontal => "Columns",
+            LayoutMode::Grid => "Grid",
+            LayoutMode::Centered => "Centered",
+            LayoutMode::Pixel => "Pixel",
+        }
+    }
+
+    fn has_labels(&self) -> bool {
+        *self != LayoutMode::Pixel
+    }
+}
+
+/// Viewport layout settings, saved with the project
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaletteLayout {
+    pub mode: LayoutMode,
+    /// Step aligned across ramps in Centered mode; None aligns each ramp's middle
+    pub anchor_step: Option<usize>,
+}
+
+/// Where a swatch's color squares go in world space
+struct SwatchPlacement {
+    /// Top-left of the first square
+    origin: Vec2,
+    /// Offset from one square to the next
+    pitch: Vec2,
+    /// Side of each square
+    square: f32,
+    count: usize,
+}
+
+impl SwatchPlacement {
+    fn square_rect(&self, index: usize) -> Rect {
+        let position = self.origin + self.pitch * index as f32;
+        Rect::new(position.x, position.y, self.square, self.square)
+    }
+
+    /// Box around the squares only
+    fn squares_rect(&self) -> Rect {
+        let last = self.square_rect(self.count.saturating_sub(1));
+        self.square_rect(0).combine_with(last)
+    }
+}
+
+impl PaletteLayout {
+    /// Place every swatch
+    fn place(&self, swatches: &[Vec<Color32>]) -> Vec<SwatchPlacement> {
+        let row_pitch = COLOR_SQUARE_SIZE + SWATCH_VERTICAL_SPACING;
+        let step_pitch = COLOR_SQUARE_SIZE + COLOR_SQUARE_SPACING;
+        let placement = |origin: Vec2, pitch: Vec2, square: f32, count: usize| SwatchPlacement {
+            origin: Vec2::new(SWATCH_START_X, SWATCH_START_Y) + origin,
+            pitch,
+            square,
+            count,
+        };
+
+        match self.mode {
+            LayoutMode::Vertical => swatches
+                .iter()
+                .enumerate()
+                .map(|(i, colors)| {
+                    let origin = Vec2::new(0.0, i as f32 * row_pitch);
+                    placement(origin, Vec2::new(step_pitch, 0.0), COLOR_SQUARE_SIZE, colors.len())
+                })
+                .collect(),
+            LayoutMode::Horizontal => swatches
+                .iter()
+                .enumerate()
+                .map(|(i, colors)| {
+                    let origin = Vec2::new(i as f32 * (COLOR_SQUARE_SIZE + STEP_LABEL_WIDTH + GRID_SPACING), 0.0);
+                    placement(origin, Vec2::new(0.0, step_pitch), COLOR_SQUARE_SIZE, colors.len())
+                })
+                .collect(),
+            LayoutMode::Grid => {
+                let columns = (swatches.len() as f32).sqrt().ceil().max(1.0) as usize;
+                let widest = swatches.iter().map(Vec::len).max().unwrap_or(0) as f32 * step_pitch;
+                let cell = Vec2::new(
+                    widest + INDEX_LABEL_OFFSET + GRID_SPACING,
+                    COLOR_SQUARE_SIZE + STEP_LABEL_HEIGHT + GRID_SPACING,
+                );
+                swatches
+                    .iter()
+                    .enumerate()
+                    .map(|(i, colors)| {
+                        let origin = Vec2::new((i % columns) as f32, (i / columns) as f32) * cell;
+                        placement(origin, Vec2::new(step_pitch, 0.0), COLOR_SQUARE_SIZE, colors.len())
+                    })
+                    .collect()
+            }
+            LayoutMode::Centered => swatches
+                .iter()
+                .enumerate()
+                .map(|(i, colors)| {
+                    let last = colors.len().saturating_sub(1);
+                    // Steps are whole squares, so the middle of an even ramp falls in a gap
+                    let anchor = match self.anchor_step {
+                        Some(step) => step.min(last) as f32,
+                        None => last as f32 / 2.0,
+                    };
+                    let origin = Vec2::new(-anchor * step_pitch, i as f32 * row_pitch);
+                    placement(origin, Vec2::new(step_pitch, 0.0), COLOR_SQUARE_SIZE, colors.len())
+                })
+                .collect(),
+            LayoutMode::Pixel => swatches
+                .iter()
+                .enumerate()
+                .map(|(i, colors)| {
+                    let origin = Vec2::new(0.0, i as f32 * PIXEL_SQUARE_SIZE);
+                    placement(origin, Vec2::new(PIXEL_SQUARE_SIZE, 0.0), PIXEL_SQUARE_SIZE, colors.len())
+                })
+                .collect(),
+        }
+    }
+
+    /// Box around a placed swatch, including its index and step labels
+    fn bounds(&self, placement: &SwatchPlacement) -> Rect {
+        let squares = placement.squares_rect();
+        match self.mode {
+            LayoutMode::Horizontal => Rect::new(
+                squares.x - STEP_LABEL_WIDTH,
+                squares.y - INDEX_LABEL_HEIGHT,
+                squares.w + STEP_LABEL_WIDTH,
+                squares.h + INDEX_LABEL_HEIGHT,
+            ),
+            LayoutMode::Pixel => squares,
+            _ => Rect::new(
+                squares.x - INDEX_LABEL_OFFSET,
+                squares.y,
+                squares.w + INDEX_LABEL_OFFSET,
+                squares.h + STEP_LABEL_HEIGHT,
+            ),
+        }
+    }
+}
+
+/// World-space box around a swatch, including its index and step labels
+pub fn swatch_bounds(layout: &PaletteLayout, swatches: &[Vec<Color32>], swatch_index: usize) -> Option<Rect> {
+    let placements = layout.place(swatches);
+    placements.get(swatch_index).map(|placement| layout.bounds(placement))
+}
+
+/// World-space box around every swatch drawn by `draw_palette`
+pub fn palette_bounds(layout: &PaletteLayout, swatches: &[Vec<Color32>]) -> Option<Rect> {
+    layout
+        .place(swatches)
+        .iter()
+        .map(|placement| layout.bounds(placement))
+        .reduce(|a, b| a.combine_with(b))
+}
+
+/// What lies under a point of the palette
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PaletteHit {
+    pub swatch: usize,
+    /// The color square, or None over the labels or the gaps between squares
+    pub color: Option<usize>,
+}
+
+/// Find the swatch (and color square) under a world position
+pub fn hit_test(layout: &PaletteLayout, world_pos: Vec2, swatches: &[Vec<Color32>]) -> Option<PaletteHit> {
+    let placements = layout.place(swatches);
+    // Squares first: a swatch's labels may overlap the next swatch's box
+    let on_square = placements.iter().enumerate().find_map(|(swatch, placement)| {
+        let color = (0..placement.count).find(|&i| placement.square_rect(i).contains(world_pos))?;
+        Some(PaletteHit {
+            swatch,
+            color: Some(color),
+        })
+    });
+    on_square.or_else(|| {
+        placements
+            .iter()
+            .position(|placement| layout.bounds(placement).contains(world_pos))
+            .map(|swatch| PaletteHit { swatch, color: None })
+    })
+}
+
+/// The slot a dragged swatch would be dropped into at a world position: the
+/// swatch whose center is nearest
+pub fn drop_slot(layout: &PaletteLayout, world_pos: Vec2, swatches: &[Vec<Color32>]) -> usize {
+    layout
+        .place(swatches)
+        .iter()
+        .map(|placement| placement.squares_rect().center().distance(world_pos))
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map_or(0, |(slot, _)| slot)
+}
+
+// =============================================================================
+// Palette rendering in world space
+// =============================================================================
+
+/// Per-step flags of a swatch that are drawn as markers on its squares
+#[derive(Clone, Debug, Default)]
+pub struct StepMarkers {
+    /// Colors beyond sRGB, which can only be drawn clipped
+    pub outside_srgb: Vec<bool>,
+    /// Steps locked at their color
+    pub locked: Vec<bool>,
+}
 
-/// Draw all swatches in the palette, auto-aligned vertically
-/// All swatches start at the same X position and stack vertically
+impl StepMarkers {
+    fn outside_srgb(&self, step: usize) -> bool {
+        self.outside_srgb.get(step).copied().unwrap_or(false)
+    }
+
+    fn locked(&self, step: usize) -> bool {
+        self.locked.get(step).copied().unwrap_or(false)
+    }
+}
+
+/// Draw all swatches in the palette, arranged by the layout, with the
+/// markers of each swatch on its squares
 pub fn draw_palette(
     viewport: &Viewport,
+    layout: &PaletteLayout,
     swatches: &[Vec<Color32>],
+    markers: &[StepMarkers],
     current_swatch_index: usize,
+    selected_color_index: usize,
 ) {
-    let mut y_offset = SWATCH_START_Y;
-    
-    for (swatch_idx, colors) in swatches.iter().enumerate() {
+    let no_markers = StepMarkers::default();
+    for (swatch_idx, (colors, placement)) in swatches.iter().zip(layout.place(swatches)).enumerate() {
         let is_selected = swatch_idx == current_swatch_index;
-        let position = Vec2::new(SWATCH_START_X, y_offset);
-        draw_swatch(viewport, colors, position, swatch_idx, is_selected);
-        
-        // Move down for next swatch
-        y_offset += COLOR_SQUARE_SIZE + SWATCH_VERTICAL_SPACING;
+        let selected_color = is_selected.then_some(selected_color_index);
+        let markers = markers.get(swatch_idx).unwrap_or(&no_markers);
+        draw_swatch(viewport, layout.mode, colors, markers, &placement, swatch_idx, selected_color);
     }
 }
 
-/// Draw a single swatch at a given world position
-/// The position marks the top-left of the first color square
+/// Outline the slot a dragged swatch will land in
+pub fn draw_drop_indicator(viewport: &Viewport, layout: &PaletteLayout, swatches: &[Vec<Color32>], to: usize) {
+    let Some(bounds) = swatch_bounds(layout, swatches, to) else {
+        return;
+    };
+    let start = viewport.world_to_screen(bounds.point());
+    let size = bounds.size() * viewport.zoom;
+    draw_rectangle_lines(start.x, start.y, size.x, size.y, 3.0, Color::new(0.4, 0.6, 1.0, 1.0));
+}
+
+/// Cells per side of the checkerboard behind translucent colors
+const TRANSPARENCY_CHECKER_CELLS: usize = 4;
+
+/// Light and dark checkerboard filling a square, so transparency is visible
+/// whatever the canvas background
+fn draw_transparency_checker(pos: Vec2, size: f32) {
+    let cell = size / TRANSPARENCY_CHECKER_CELLS as f32;
+    for row in 0..TRANSPARENCY_CHECKER_CELLS {
+        for col in 0..TRANSPARENCY_CHECKER_CELLS {
+            let shade = if (row + col) % 2 == 0 { 0.8 } else { 0.5 };
+            draw_rectangle(
+                pos.x + col as f32 * cell,
+                pos.y + row as f32 * cell,
+                cell,
+                cell,
+                Color::new(shade, shade, shade, 1.0),
+            );
+        }
+    }
+}
+
+/// Largest size of the out-of-sRGB corner marker, in pixels
+const GAMUT_MARKER_SIZE: f32 = 10.0;
+
+/// Black and white triangle in the top-right corner of a square
+fn draw_gamut_marker(pos: Vec2, size: f32) {
+    let marker = (size * 0.3).min(GAMUT_MARKER_SIZE);
+    let corner = Vec2::new(pos.x + size, pos.y);
+    let left = corner - Vec2::new(marker, 0.0);
+    let below = corner + Vec2::new(0.0, marker);
+    draw_triangle(corner, left, below, WHITE);
+    draw_triangle_lines(corner, left, below, 1.0, BLACK);
+}
+
+/// Largest size of the lock marker, in pixels
+const LOCK_MARKER_SIZE: f32 = 10.0;
+
+/// Small padlock in the bottom-left corner of a square
+fn draw_lock_marker(pos: Vec2, size: f32) {
+    let marker = (size * 0.3).min(LOCK_MARKER_SIZE);
+    let inset = marker * 0.3;
+    let body = Rect::new(pos.x + inset, pos.y + size - inset - marker * 0.6, marker, marker * 0.6);
+    // Shackle: a ring whose lower half the body covers
+    let shackle_radius = marker * 0.3;
+    draw_circle_lines(body.x + body.w / 2.0, body.y, shackle_radius, 1.5, BLACK);
+    draw_rectangle(body.x, body.y, body.w, body.h, WHITE);
+    draw_rectangle_lines(body.x, body.y, body.w, body.h, 1.0, BLACK);
+}
+
+/// Draw a single placed swatch.
+/// `selected_color` is Some for the current swatch.
 fn draw_swatch(
     viewport: &Viewport,
+    mode: LayoutMode,
     colors: &[Color32],
-    position: Vec2,
+    markers: &StepMarkers,
+    placement: &SwatchPlacement,
     swatch_index: usize,
-    is_selected: bool,
+    selected_color: Option<usize>,
 ) {
-    let num_colors = colors.len();
-    if num_colors == 0 {
+    let is_selected = selected_color.is_some();
+    if colors.is_empty() {
         return;
     }
 
-    // All swatches are left-aligned, starting at the same X position
-    let start_x = position.x;
-    let start_y = position.y;
-    let total_width =
-        num_colors as f32 * (COLOR_SQUARE_SIZE + COLOR_SQUARE_SPACING) - COLOR_SQUARE_SPACING;
-
     // Draw selection highlight if this is the current swatch
     if is_selected {
-        let screen_start = viewport.world_to_screen(Vec2::new(start_x - 4.0, start_y - 4.0));
-        let screen_end = viewport.world_to_screen(Vec2::new(
-            start_x + total_width + 4.0,
-            start_y + COLOR_SQUARE_SIZE + 4.0,
-        ));
+        let squares = placement.squares_rect();
+        let screen_start = viewport.world_to_screen(squares.point() - Vec2::splat(4.0));
+        let screen_end = viewport.world_to_screen(squares.point() + squares.size() + Vec2::splat(4.0));
         let size = screen_end - screen_start;
         draw_rectangle_lines(
             screen_start.x,
@@ -122,57 +423,87 @@ fn draw_swatch(
         );
     }
 
-    // Draw swatch index to the left of the first color
-    let index_pos = viewport.world_to_screen(Vec2::new(start_x - INDEX_LABEL_OFFSET, start_y + COLOR_SQUARE_SIZE / 2.0));
-    let font_size = (16.0 * viewport.zoom).max(12.0) as u16;
-    let index_color = if is_selected {
-        Color::new(0.4, 0.7, 1.0, 1.0) // Highlight color for selected swatch
-    } else {
-        Color::new(0.6, 0.6, 0.6, 1.0)
-    };
-    draw_text(
-        &format!("{}", swatch_index + 1),
-        index_pos.x,
-        index_pos.y + font_size as f32 / 3.0,
-        font_size as f32,
-        index_color,
-    );
+    if mode.has_labels() {
+        // Draw swatch index to the left of the first color (above it for columns)
+        let first = placement.square_rect(0);
+        let index_world = if mode == LayoutMode::Horizontal {
+            Vec2::new(first.x + first.w / 2.0 - 4.0, first.y - INDEX_LABEL_HEIGHT / 2.0)
+        } else {
+            Vec2::new(first.x - INDEX_LABEL_OFFSET, first.y + first.h / 2.0)
+        };
+        let index_pos = viewport.world_to_screen(index_world);
+        let font_size = (16.0 * viewport.zoom).max(12.0) as u16;
+        let index_color = if is_selected {
+            Color::new(0.4, 0.7, 1.0, 1.0) // Highlight color for selected swatch
+        } else {
+            Color::new(0.6, 0.6, 0.6, 1.0)
+        };
+        draw_text(
+            &format!("{}", swatch_index + 1),
+            index_pos.x,
+            index_pos.y + font_size as f32 / 3.0,
+            font_size as f32,
+            index_color,
+        );
+    }
 
     for (i, color) in colors.iter().enumerate() {
-        // World position of this square
-        let world_x = start_x + i as f32 * (COLOR_SQUARE_SIZE + COLOR_SQUARE_SPACING);
-        let world_y = start_y;
-
-        // Convert to screen coordinates
-        let screen_pos = viewport.world_to_screen(Vec2::new(world_x, world_y));
-        let screen_size = COLOR_SQUARE_SIZE * viewport.zoom;
-
-        // Draw the color square
-        draw_rectangle(
-            screen_pos.x,
-            screen_pos.y,
-            screen_size,
-            screen_size,
-            Color::from_rgba(color.r(), color.g(), color.b(), color.a()),
-        );
+        // World position of this square, converted to screen coordinates
+        let square = placement.square_rect(i);
+        let screen_pos = viewport.world_to_screen(square.point());
+        let screen_size = square.w * viewport.zoom;
 
-        // Draw border for better visibility
-        draw_rectangle_lines(
-            screen_pos.x,
-            screen_pos.y,
-            screen_size,
-            screen_size,
-            2.0,
-            Color::new(0.2, 0.2, 0.2, 0.8),
-        );
+        // Draw the color square, over a checkerboard when it's translucent
+        if !color.is_opaque() {
+            draw_transparency_checker(screen_pos, screen_size);
+        }
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        draw_rectangle(screen_pos.x, screen_pos.y, screen_size, screen_size, Color::from_rgba(r, g, b, a));
+
+        // Mark colors the canvas can't show exactly with a corner notch
+        if markers.outside_srgb(i) {
+            draw_gamut_marker(screen_pos, screen_size);
+        }
+        if markers.locked(i) {
+            draw_lock_marker(screen_pos, screen_size);
+        }
+
+        // Draw border for better visibility (pixel mode is drawn seamless)
+        if mode != LayoutMode::Pixel {
+            draw_rectangle_lines(
+                screen_pos.x,
+                screen_pos.y,
+                screen_size,
+                screen_size,
+                2.0,
+                Color::new(0.2, 0.2, 0.2, 0.8),
+            );
+        }
+
+        // Outline the selected color of the current swatch
+        if selected_color == Some(i) {
+            draw_rectangle_lines(
+                screen_pos.x - 2.0,
+                screen_pos.y - 2.0,
+                screen_size + 4.0,
+                screen_size + 4.0,
+                2.0,
+                WHITE,
+            );
+        }
+
+        if !mode.has_labels() {
+            continue;
+        }
 
-        // Draw index number below
+        // Draw index number below (left of the square for columns)
         let idx_font_size = (14.0 * viewport.zoom).max(10.0) as u16;
         let label = format!("{}", i + 1);
-        let text_pos = viewport.world_to_screen(Vec2::new(
-            world_x + COLOR_SQUARE_SIZE / 2.0,
-            world_y + COLOR_SQUARE_SIZE + 8.0,
-        ));
+        let text_pos = if mode == LayoutMode::Horizontal {
+            viewport.world_to_screen(Vec2::new(square.x - STEP_LABEL_WIDTH, square.y + square.h / 2.0 - 8.0))
+        } else {
+            viewport.world_to_screen(Vec2::new(square.x + square.w / 2.0, square.y + square.h + 8.0))
+        };
         draw_text(
             &label,
             text_pos.x - 4.0,
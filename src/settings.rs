@@ -0,0 +1,90 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::background::BackgroundSettings;
+use crate::project::write_atomically;
+
+// =============================================================================
+// Settings: user preferences that persist across sessions
+// =============================================================================
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Length of the File > Recent Files list
+const MAX_RECENT_FILES: usize = 10;
+
+/// Directory for settings and session files (e.g. `~/.local/share/chrmapal`).
+/// None if the platform has no data directory.
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("chrmapal"))
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub autosave_enabled: bool,
+    /// Seconds between autosaves
+    pub autosave_interval: u32,
+    /// Recently opened or saved projects, most recent first
+    pub recent_files: Vec<PathBuf>,
+    /// What the canvas is drawn over
+    pub background: BackgroundSettings,
+    /// A change made with a control that may still be dragged
+    #[serde(skip)]
+    save_pending: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            autosave_enabled: true,
+            autosave_interval: 60,
+            recent_files: Vec::new(),
+            background: BackgroundSettings::default(),
+            save_pending: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Load the saved settings, falling back to the defaults
+    pub fn load() -> Self {
+        data_dir()
+            .and_then(|dir| fs::read_to_string(dir.join(SETTINGS_FILE)).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// Move `path` to the top of the recent files and save the settings
+    pub fn add_recent_file(&mut self, path: &Path) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.recent_files.retain(|recent| *recent != path);
+        self.recent_files.insert(0, path);
+        self.recent_files.truncate(MAX_RECENT_FILES);
+        self.save();
+    }
+
+    /// Save once the pointer is released, so dragging a slider or color
+    /// picker doesn't write the file on every frame
+    pub fn save_when_released(&mut self) {
+        self.save_pending = true;
+    }
+
+    /// Write a change from `save_when_released` unless a drag is still going on
+    pub fn save_pending(&mut self, pointer_down: bool) {
+        if self.save_pending && !pointer_down {
+            self.save_pending = false;
+            self.save();
+        }
+    }
+
+    /// Save the settings; failures are ignored, as they only lose preferences
+    pub fn save(&self) {
+        let Some(dir) = data_dir() else { return };
+        if let Ok(text) = serde_json::to_string_pretty(self) {
+            let _ = fs::create_dir_all(&dir).and_then(|()| write_atomically(&dir.join(SETTINGS_FILE), &text));
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::fs;
+use std::path::PathBuf;
+
+use egui_macroquad::egui::Color32;
+
+use crate::app::App;
+use crate::color::ColorSpace;
+use crate::curves::CurveType;
+use crate::palette::{ControlPoint, Palette, StepNaming, Swatch};
+use crate::project::{Project, ProjectError};
+use crate::settings::data_dir;
+
+// =============================================================================
+// Palette templates: starting points for File > New Palette
+// =============================================================================
+//
+// Built-in templates are defined here as control points, so they regenerate
+// like any hand-made swatch. User templates are ordinary project files kept in
+// the `templates` folder of the data directory.
+
+/// Extension of project files, shared by user templates
+pub const PROJECT_EXTENSION: &str = "chrmapal";
+
+/// Templates that ship with the app
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum BuiltinTemplate {
+    /// One swatch with no control points, ready to pin colors into
+    Empty,
+    #[default]
+    GreyRamp,
+    SkinTones,
+    Terrain,
+    UiMaterial,
+}
+
+impl BuiltinTemplate {
+    pub const ALL: &'static [BuiltinTemplate] = &[
+        BuiltinTemplate::Empty,
+        BuiltinTemplate::GreyRamp,
+        BuiltinTemplate::SkinTones,
+        BuiltinTemplate::Terrain,
+        BuiltinTemplate::UiMaterial,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuiltinTemplate::Empty => "Empty",
+            BuiltinTemplate::GreyRamp => "Grey Ramp",
+            BuiltinTemplate::SkinTones => "Skin Tones",
+            BuiltinTemplate::Terrain => "Terrain",
+            BuiltinTemplate::UiMaterial => "UI Material",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            BuiltinTemplate::Empty => "A single swatch without control points",
+            BuiltinTemplate::GreyRamp => "One neutral ramp from white to black",
+            BuiltinTemplate::SkinTones => "Five skin ramps from light to deep",
+            BuiltinTemplate::Terrain => "Grass, dirt, stone, sand and water",
+            BuiltinTemplate::UiMaterial => "Primary, neutral and status colors with Tailwind steps",
+        }
+    }
+
+    pub fn palette(&self) -> Palette {
+        let swatches = match self {
+            BuiltinTemplate::Empty => vec![Swatch::new(8, Vec::new(), CurveType::default(), ColorSpace::OkLab)],
+            BuiltinTemplate::GreyRamp => vec![ramp("grey", 9, &[(0.0, [245, 245, 245]), (1.0, [16, 16, 16])])],
+            BuiltinTemplate::SkinTones => vec![
+                ramp("skin-porcelain", 6, &[(0.0, [255, 236, 222]), (1.0, [150, 98, 80])]),
+                ramp("skin-light", 6, &[(0.0, [248, 214, 186]), (1.0, [128, 78, 58])]),
+                ramp("skin-tan", 6, &[(0.0, [224, 172, 128]), (1.0, [96, 58, 40])]),
+                ramp("skin-brown", 6, &[(0.0, [170, 112, 76]), (1.0, [60, 36, 28])]),
+                ramp("skin-deep", 6, &[(0.0, [120, 76, 54]), (1.0, [34, 22, 20])]),
+            ],
+            BuiltinTemplate::Terrain => vec![
+                ramp("grass", 6, &[(0.0, [190, 220, 110]), (1.0, [28, 60, 30])]),
+                ramp("dirt", 6, &[(0.0, [214, 170, 120]), (1.0, [60, 38, 26])]),
+                ramp("stone", 6, &[(0.0, [200, 200, 196]), (1.0, [40, 42, 48])]),
+                ramp("sand", 6, &[(0.0, [248, 232, 180]), (1.0, [140, 110, 60])]),
+                ramp("water", 6, &[(0.0, [150, 220, 240]), (1.0, [16, 40, 90])]),
+            ],
+            // Saturated at 500, like Material and Tailwind scales
+            BuiltinTemplate::UiMaterial => vec![
+                ramp("primary", 11, &[(0.0, [238, 240, 255]), (0.5, [79, 70, 229]), (1.0, [30, 27, 75])]),
+                ramp("neutral", 11, &[(0.0, [250, 250, 250]), (0.5, [115, 115, 115]), (1.0, [10, 10, 10])]),
+                ramp("success", 11, &[(0.0, [240, 253, 244]), (0.5, [34, 197, 94]), (1.0, [5, 46, 22])]),
+                ramp("warning", 11, &[(0.0, [255, 251, 235]), (0.5, [245, 158, 11]), (1.0, [69, 26, 3])]),
+                ramp("error", 11, &[(0.0, [254, 242, 242]), (0.5, [239, 68, 68]), (1.0, [69, 10, 10])]),
+            ],
+        };
+
+        let mut palette = Palette::default();
+        if *self == BuiltinTemplate::UiMaterial {
+            palette.step_naming = StepNaming::Tailwind;
+        }
+        for swatch in swatches {
+            palette.add_swatch(swatch);
+        }
+        palette
+    }
+
+    /// Replace the app's palette with this template
+    pub fn apply(&self, app: &mut App) {
+        *app = App::new();
+        app.palette = self.palette();
+        app.regenerate_all_colors();
+    }
+}
+
+/// A named OkLCh swatch through the given `(position, rgb)` control points
+fn ramp(name: &str, size: usize, points: &[(f32, [u8; 3])]) -> Swatch {
+    let control_points = points
+        .iter()
+        .enumerate()
+        .map(|(id, &(position, [r, g, b]))| ControlPoint::new(id as u32, position, Color32::from_rgb(r, g, b)))
+        .collect();
+    let mut swatch = Swatch::new(size, control_points, CurveType::default(), ColorSpace::OkLCh);
+    swatch.name = name.to_string();
+    swatch
+}
+
+// =============================================================================
+// User templates
+// =============================================================================
+
+/// A template saved by the user
+pub struct UserTemplate {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl UserTemplate {
+    /// Replace the app's palette with this template. The result is a new,
+    /// unsaved palette rather than the template file itself.
+    pub fn apply(&self, app: &mut App) -> Result<(), ProjectError> {
+        let project = Project::load(&self.path)?;
+        *app = App::new();
+        project.apply(app);
+        Ok(())
+    }
+
+    pub fn delete(&self) -> std::io::Result<()> {
+        fs::remove_file(&self.path)
+    }
+}
+
+fn templates_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("templates"))
+}
+
+/// The user's templates, sorted by name
+pub fn user_templates() -> Vec<UserTemplate> {
+    let Some(entries) = templates_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut templates: Vec<UserTemplate> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == PROJECT_EXTENSION))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            Some(UserTemplate { name, path })
+        })
+        .collect();
+    templates.sort_by_key(|template| template.name.to_lowercase());
+    templates
+}
+
+/// Save the app's palette as a user template, replacing any with the same name
+pub fn save_user_template(name: &str, app: &App) -> Result<(), ProjectError> {
+    let file_name: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let Some(dir) = templates_dir() else {
+        return Err(ProjectError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no data directory to store templates in",
+        )));
+    };
+    fs::create_dir_all(&dir)?;
+    Project::from_app(app).save(dir.join(format!("{file_name}.{PROJECT_EXTENSION}")))
+}
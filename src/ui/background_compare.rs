@@ -0,0 +1,87 @@
+use egui_macroquad::egui::{self, vec2, Color32, Rect, ScrollArea, Sense};
+
+use crate::app::App;
+use crate::background::default_comparison_backgrounds;
+use crate::settings::Settings;
+
+/// Side of each color square in the comparison
+const SQUARE_SIZE: f32 = 14.0;
+/// Background visible around the squares
+const SURFACE_PADDING: f32 = 10.0;
+/// Most reference backgrounds the window offers
+const MAX_BACKGROUNDS: usize = 8;
+
+/// Draw every swatch over each reference background, side by side
+pub fn draw_background_compare(egui_ctx: &egui::Context, app: &App, settings: &mut Settings, open: &mut bool) {
+    egui::Window::new("Compare on Backgrounds")
+        .open(open)
+        .default_width(560.0)
+        .show(egui_ctx, |ui| {
+            let backgrounds = &mut settings.background.comparison;
+            let mut changed = false;
+            ui.horizontal(|ui| {
+                ui.label("Backgrounds:");
+                let mut remove = None;
+                for (i, color) in backgrounds.iter_mut().enumerate() {
+                    let response = ui.color_edit_button_srgba(color);
+                    changed |= response.changed();
+                    if response.secondary_clicked() {
+                        remove = Some(i);
+                    }
+                }
+                if let Some(i) = remove.filter(|_| backgrounds.len() > 1) {
+                    backgrounds.remove(i);
+                    changed = true;
+                }
+                if backgrounds.len() < MAX_BACKGROUNDS && ui.small_button("+").clicked() {
+                    backgrounds.push(Color32::from_gray(128));
+                    changed = true;
+                }
+                if ui.small_button("Reset").clicked() {
+                    *backgrounds = default_comparison_backgrounds();
+                    changed = true;
+                }
+            });
+            ui.weak("Right-click a background to remove it");
+            if changed {
+                settings.save_when_released();
+            }
+            ui.separator();
+
+            let backgrounds = &settings.background.comparison;
+            ScrollArea::both().show(ui, |ui| {
+                for (index, colors) in app.generated_colors.iter().enumerate() {
+                    let swatch = &app.palette.swatches[index];
+                    let name = swatch.display_name(index);
+                    if index == app.current_swatch_index {
+                        ui.strong(name);
+                    } else {
+                        ui.label(name);
+                    }
+                    ui.horizontal(|ui| {
+                        for &background in backgrounds {
+                            draw_surface(ui, background, colors);
+                        }
+                    });
+                }
+            });
+        });
+}
+
+/// A ramp over a patch of background color
+fn draw_surface(ui: &mut egui::Ui, background: Color32, colors: &[Color32]) {
+    let size = vec2(
+        colors.len() as f32 * SQUARE_SIZE + 2.0 * SURFACE_PADDING,
+        SQUARE_SIZE + 2.0 * SURFACE_PADDING,
+    );
+    let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+    if !ui.is_rect_visible(rect) {
+        return;
+    }
+    let painter = ui.painter();
+    painter.rect_filled(rect, 0.0, background);
+    for (i, &color) in colors.iter().enumerate() {
+        let min = rect.min + vec2(SURFACE_PADDING + i as f32 * SQUARE_SIZE, SURFACE_PADDING);
+        painter.rect_filled(Rect::from_min_size(min, vec2(SQUARE_SIZE, SQUARE_SIZE)), 0.0, color);
+    }
+}
@@ -0,0 +1,216 @@
+use egui_macroquad::egui::{self, Button, Frame, Id, Key, Order, Pos2};
+use macroquad::input::{is_mouse_button_down, is_mouse_button_pressed, mouse_position, MouseButton};
+use macroquad::math::{Rect, Vec2};
+
+use crate::app::App;
+use crate::clipboard::{self, ColorFormat};
+use crate::rendering::{drop_slot, hit_test, PaletteHit};
+use crate::ui::swatch_editor::SwatchEditorState;
+
+/// Distance in pixels the mouse must move before a press on a swatch becomes a drag
+const DRAG_THRESHOLD: f32 = 4.0;
+/// Free areas narrower or shorter than this aren't worth framing content in
+const MIN_VISIBLE_SIZE: f32 = 120.0;
+
+/// A swatch held by the left mouse button
+struct SwatchDrag {
+    swatch: usize,
+    /// Screen position of the press
+    start: Vec2,
+    /// Slot it would be dropped into; None until the mouse has moved far enough
+    target: Option<usize>,
+}
+
+/// Right-click menu for a swatch
+struct ContextMenu {
+    hit: PaletteHit,
+    position: Pos2,
+}
+
+/// Mouse interaction state for the palette drawn in the viewport
+#[derive(Default)]
+pub struct CanvasState {
+    drag: Option<SwatchDrag>,
+    context_menu: Option<ContextMenu>,
+}
+
+impl CanvasState {
+    /// Source and destination of the swatch being dragged
+    pub fn drop_target(&self) -> Option<(usize, usize)> {
+        let drag = self.drag.as_ref()?;
+        drag.target.map(|target| (drag.swatch, target))
+    }
+}
+
+/// Select swatches and colors by clicking them, reorder swatches by dragging
+/// and open the context menu with a right click. Returns true while the mouse
+/// is held on a swatch, so the viewport doesn't pan.
+pub fn handle_canvas_input(
+    app: &mut App,
+    state: &mut CanvasState,
+    swatch_state: &mut SwatchEditorState,
+    egui_wants_pointer: bool,
+) -> bool {
+    let mouse = Vec2::from(mouse_position());
+
+    if let Some(drag) = &mut state.drag {
+        if is_mouse_button_down(MouseButton::Left) {
+            if drag.target.is_some() || mouse.distance(drag.start) > DRAG_THRESHOLD {
+                let world = app.viewport.screen_to_world(mouse);
+                drag.target = Some(drop_slot(&app.layout, world, &app.generated_colors));
+            }
+            return true;
+        }
+        // Released
+        if let Some((from, to)) = state.drop_target() {
+            app.move_swatch(from, to);
+            swatch_state.sync_with_swatch(app);
+        }
+        state.drag = None;
+        return true;
+    }
+
+    if egui_wants_pointer {
+        return false;
+    }
+    let left = is_mouse_button_pressed(MouseButton::Left);
+    let right = is_mouse_button_pressed(MouseButton::Right);
+    if !left && !right {
+        return false;
+    }
+    let Some(hit) = hit_test(&app.layout, app.viewport.screen_to_world(mouse), &app.generated_colors) else {
+        return false;
+    };
+
+    app.select_swatch(hit.swatch);
+    if let Some(color) = hit.color {
+        app.select_color(color);
+    }
+    swatch_state.sync_with_swatch(app);
+
+    if left {
+        state.drag = Some(SwatchDrag {
+            swatch: hit.swatch,
+            start: mouse,
+            target: None,
+        });
+    } else {
+        state.context_menu = Some(ContextMenu {
+            hit,
+            position: Pos2::new(mouse.x, mouse.y),
+        });
+    }
+    true
+}
+
+/// The largest part of the screen covered by neither panels nor windows,
+/// where framing commands place the palette. Call after drawing the UI.
+pub fn visible_canvas_area(egui_ctx: &egui::Context) -> Rect {
+    let windows: Vec<egui::Rect> = egui_ctx.memory(|mem| {
+        mem.areas()
+            .visible_layer_ids()
+            .into_iter()
+            .filter(|layer| layer.order == Order::Middle)
+            .filter_map(|layer| mem.area_rect(layer.id))
+            .collect()
+    });
+
+    // Maximal free rectangles: cut every free rectangle a window overlaps into
+    // the strips on each side of the window
+    let available = egui_ctx.available_rect();
+    let mut free = vec![available];
+    for window in windows {
+        let mut next = Vec::new();
+        for rect in free {
+            if !rect.intersects(window) {
+                next.push(rect);
+                continue;
+            }
+            let strips = [
+                egui::Rect::from_x_y_ranges(rect.left()..=window.left(), rect.y_range()),
+                egui::Rect::from_x_y_ranges(window.right()..=rect.right(), rect.y_range()),
+                egui::Rect::from_x_y_ranges(rect.x_range(), rect.top()..=window.top()),
+                egui::Rect::from_x_y_ranges(rect.x_range(), window.bottom()..=rect.bottom()),
+            ];
+            next.extend(
+                strips
+                    .into_iter()
+                    .filter(|strip| strip.width() >= MIN_VISIBLE_SIZE && strip.height() >= MIN_VISIBLE_SIZE),
+            );
+        }
+        free = next;
+    }
+
+    let best = free
+        .into_iter()
+        .max_by(|a, b| a.area().total_cmp(&b.area()))
+        .unwrap_or(available);
+    Rect::new(best.left(), best.top(), best.width(), best.height())
+}
+
+/// Draw the swatch context menu opened by `handle_canvas_input`
+pub fn draw_canvas_context_menu(
+    egui_ctx: &egui::Context,
+    app: &mut App,
+    state: &mut CanvasState,
+    swatch_state: &mut SwatchEditorState,
+) {
+    let Some(menu) = &state.context_menu else {
+        return;
+    };
+    let hit = menu.hit;
+    let mut close = egui_ctx.input(|i| i.key_pressed(Key::Escape));
+    let mut changed = false;
+
+    let response = egui::Area::new(Id::new("canvas_context_menu"))
+        .order(Order::Foreground)
+        .fixed_pos(menu.position)
+        .show(egui_ctx, |ui| {
+            Frame::menu(ui.style()).show(ui, |ui| {
+                if ui.button("Duplicate Swatch").clicked() {
+                    app.duplicate_swatch(hit.swatch);
+                    app.select_swatch(hit.swatch + 1);
+                    changed = true;
+                }
+                if ui.add_enabled(app.swatch_count() > 1, Button::new("Delete Swatch")).clicked() {
+                    app.remove_swatch(hit.swatch);
+                    changed = true;
+                }
+                ui.separator();
+                if ui.button("Copy Swatch").clicked() {
+                    clipboard::copy_swatch(app);
+                    close = true;
+                }
+                if ui.button("Copy as Hex List").clicked() {
+                    clipboard::copy_swatch_colors(app);
+                    close = true;
+                }
+                let color = hit
+                    .color
+                    .and_then(|index| app.generated_colors.get(hit.swatch)?.get(index).copied());
+                if let Some(color) = color {
+                    ui.separator();
+                    for format in ColorFormat::ALL {
+                        let text = format.format(color);
+                        if ui.button(format!("Copy {text}")).clicked() {
+                            clipboard::copy_selected_color(app, *format);
+                            close = true;
+                        }
+                    }
+                }
+            });
+        });
+
+    // Clicking anywhere else dismisses the menu
+    close |= egui_ctx.input(|i| {
+        i.pointer.any_pressed()
+            && i.pointer.interact_pos().is_some_and(|pos| !response.response.rect.contains(pos))
+    });
+
+    if changed {
+        swatch_state.sync_with_swatch(app);
+    }
+    if close || changed {
+        state.context_menu = None;
+    }
+}
@@ -0,0 +1,189 @@
+use egui_macroquad::egui::{self, Align2, Key, Layout, Modifiers, Order, ScrollArea, TextEdit};
+
+use crate::app::App;
+use crate::export::ExportFormat;
+use crate::keymap::{shortcut_text, Action, Keymap};
+use crate::ui::export_window::ExportState;
+use crate::ui::shortcuts::perform_action;
+use crate::ui::swatch_editor::SwatchEditorState;
+use crate::ui::top_panel::OpenWindows;
+
+const PALETTE_WIDTH: f32 = 380.0;
+const LIST_HEIGHT: f32 = 300.0;
+
+/// Something the command palette can run
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Command {
+    Action(Action),
+    /// Open the export window with a format selected
+    ExportAs(ExportFormat),
+}
+
+impl Command {
+    /// Every command, in the order listed for an empty query
+    fn all() -> Vec<Command> {
+        let actions = Action::ALL
+            .iter()
+            .filter(|&&action| action != Action::CommandPalette)
+            .map(|&action| Command::Action(action));
+        let exports = ExportFormat::ALL.iter().map(|&format| Command::ExportAs(format));
+        actions.chain(exports).collect()
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Command::Action(action) => action.name().to_string(),
+            Command::ExportAs(format) => format!("Export as {}", format.name()),
+        }
+    }
+
+    fn shortcut(&self, keymap: &Keymap) -> Option<String> {
+        match self {
+            Command::Action(action) => keymap.shortcuts(*action).first().map(shortcut_text),
+            Command::ExportAs(_) => None,
+        }
+    }
+}
+
+/// Score `text` against a fuzzy `query`: every query character must appear in
+/// order. Matches at word starts and runs of consecutive matches score higher.
+/// Returns None if the text doesn't match.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let offset = text[position..].iter().position(|&c| c == wanted)?;
+        let index = position + offset;
+        score += 1;
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 8;
+        }
+        if previous_match.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        // Skipped characters count against the match, a little
+        score -= offset.min(10) as i32 / 2;
+        previous_match = Some(index);
+        position = index + 1;
+    }
+    Some(score)
+}
+
+/// UI state for the command palette
+#[derive(Default)]
+pub struct CommandPaletteState {
+    query: String,
+    /// Highlighted row of the filtered list
+    selected: usize,
+}
+
+/// Draw the command palette overlay (opened by Ctrl+P) and run the chosen command
+pub fn draw_command_palette(
+    egui_ctx: &egui::Context,
+    app: &mut App,
+    keymap: &Keymap,
+    state: &mut CommandPaletteState,
+    swatch_state: &mut SwatchEditorState,
+    export_state: &mut ExportState,
+    windows: &mut OpenWindows,
+) {
+    if !windows.command_palette {
+        return;
+    }
+
+    // Take the navigation keys before the text field sees them
+    let (up, down, enter, escape) = egui_ctx.input_mut(|i| {
+        (
+            i.consume_key(Modifiers::NONE, Key::ArrowUp),
+            i.consume_key(Modifiers::NONE, Key::ArrowDown),
+            i.consume_key(Modifiers::NONE, Key::Enter),
+            i.consume_key(Modifiers::NONE, Key::Escape),
+        )
+    });
+
+    let mut chosen: Option<Command> = None;
+    let response = egui::Window::new("Command Palette")
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .order(Order::Foreground)
+        .anchor(Align2::CENTER_TOP, [0.0, 60.0])
+        .fixed_size([PALETTE_WIDTH, 0.0])
+        .show(egui_ctx, |ui| {
+            let search = ui.add(
+                TextEdit::singleline(&mut state.query)
+                    .hint_text("Type a command...")
+                    .desired_width(f32::INFINITY),
+            );
+            search.request_focus();
+            if search.changed() {
+                state.selected = 0;
+            }
+
+            let mut matches: Vec<(Command, i32)> = Command::all()
+                .into_iter()
+                .filter_map(|command| fuzzy_score(&state.query, &command.label()).map(|score| (command, score)))
+                .collect();
+            // Stable, so equal scores keep the menu order
+            matches.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+
+            if up {
+                state.selected = state.selected.saturating_sub(1);
+            }
+            if down {
+                state.selected += 1;
+            }
+            state.selected = state.selected.min(matches.len().saturating_sub(1));
+            if enter {
+                chosen = matches.get(state.selected).map(|&(command, _)| command);
+            }
+
+            ui.separator();
+            if matches.is_empty() {
+                ui.weak("No matching commands");
+            }
+            ScrollArea::vertical().max_height(LIST_HEIGHT).show(ui, |ui| {
+                for (row, (command, _)) in matches.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let is_selected = row == state.selected;
+                        let label = ui.selectable_label(is_selected, command.label());
+                        if is_selected && (up || down) {
+                            label.scroll_to_me(None);
+                        }
+                        if label.clicked() {
+                            chosen = Some(*command);
+                        }
+                        if let Some(shortcut) = command.shortcut(keymap) {
+                            ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                ui.weak(shortcut);
+                            });
+                        }
+                    });
+                }
+            });
+        });
+
+    // Clicking anywhere else dismisses the palette
+    let clicked_outside = response.is_some_and(|response| {
+        egui_ctx.input(|i| {
+            i.pointer.any_pressed()
+                && i.pointer.interact_pos().is_some_and(|pos| !response.response.rect.contains(pos))
+        })
+    });
+
+    if chosen.is_some() || escape || clicked_outside {
+        windows.command_palette = false;
+        state.query.clear();
+        state.selected = 0;
+    }
+    match chosen {
+        Some(Command::Action(action)) => perform_action(action, app, swatch_state, windows),
+        Some(Command::ExportAs(format)) => {
+            export_state.set_format(format);
+            windows.export = true;
+        }
+        None => {}
+    }
+}
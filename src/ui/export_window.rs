@@ -0,0 +1,185 @@
+use egui_macroquad::egui::{self, Color32, ScrollArea, Slider};
+
+use crate::app::App;
+use crate::color::{Rounding, Srgba};
+use crate::export::{export_palette, gradient_export_stops, ChannelDepth, ExportFormat, ExportSource};
+use crate::gradient::DEFAULT_GRADIENT_TOLERANCE;
+
+/// Height of the exported text preview
+const PREVIEW_HEIGHT: f32 = 160.0;
+
+/// UI state for the export window
+pub struct ExportState {
+    pub format: ExportFormat,
+    /// Export the de-duplicated color list instead of every generated color
+    pub merge_duplicates: bool,
+    /// Write linear-light values in shader formats, and in code formats at
+    /// 16-bit or float depth
+    pub linear: bool,
+    /// How colors are quantized to 8 or 16 bits
+    pub rounding: Rounding,
+    /// Channel values of code and data formats
+    pub depth: ChannelDepth,
+    /// Largest ΔE gradient formats may stray from the swatch
+    pub gradient_tolerance: f32,
+    /// Destination file
+    pub path: String,
+    /// Result of the last export
+    status: Option<String>,
+    /// Output colors of every swatch, and the app revision they were made at
+    colors: Option<(u64, Vec<Vec<Srgba>>)>,
+}
+
+impl Default for ExportState {
+    fn default() -> Self {
+        let format = ExportFormat::default();
+        Self {
+            format,
+            merge_duplicates: true,
+            linear: false,
+            rounding: Rounding::default(),
+            depth: ChannelDepth::default(),
+            gradient_tolerance: DEFAULT_GRADIENT_TOLERANCE,
+            path: format!("palette.{}", format.extension()),
+            status: None,
+            colors: None,
+        }
+    }
+}
+
+impl ExportState {
+    /// Switch formats, keeping the destination file name in step
+    pub fn set_format(&mut self, format: ExportFormat) {
+        self.format = format;
+        self.path = with_extension(&self.path, format.extension());
+    }
+
+    /// Output colors of every swatch, regenerated only when the palette changes
+    fn output_colors(&mut self, app: &App) -> Vec<Vec<Srgba>> {
+        match &self.colors {
+            Some((revision, colors)) if *revision == app.revision => colors.clone(),
+            _ => {
+                let colors: Vec<Vec<Srgba>> =
+                    (0..app.palette.swatches.len()).map(|i| app.palette.output_colors(i)).collect();
+                self.colors = Some((app.revision, colors.clone()));
+                colors
+            }
+        }
+    }
+}
+
+/// Replace the export extension of `path` (which may have two parts, like
+/// `.tokens.json`) with `extension`
+fn with_extension(path: &str, extension: &str) -> String {
+    let mut known: Vec<&str> = ExportFormat::ALL.iter().map(|f| f.extension()).collect();
+    known.sort_by_key(|ext| std::cmp::Reverse(ext.len()));
+    let stem = known
+        .iter()
+        .find_map(|ext| path.strip_suffix(&format!(".{ext}")))
+        .unwrap_or(path);
+    format!("{stem}.{extension}")
+}
+
+/// Draw the export window
+pub fn draw_export_window(egui_ctx: &egui::Context, app: &App, state: &mut ExportState, open: &mut bool) {
+    egui::Window::new("Export")
+        .open(open)
+        .default_width(320.0)
+        .show(egui_ctx, |ui| {
+            egui::ComboBox::from_label("Format")
+                .selected_text(state.format.name())
+                .show_ui(ui, |ui| {
+                    for &format in ExportFormat::ALL {
+                        if ui.selectable_label(state.format == format, format.name()).clicked() {
+                            state.set_format(format);
+                        }
+                    }
+                });
+
+            let colors = state.output_colors(app);
+            let mut source = ExportSource::from_app(app, colors, state.merge_duplicates);
+            source.linear = state.linear;
+            source.rounding = state.rounding;
+            source.depth = state.depth;
+            source.gradient_tolerance = state.gradient_tolerance;
+            if state.format.is_flat() {
+                ui.checkbox(&mut state.merge_duplicates, "Merge duplicate colors");
+
+                let count = source.flat_colors().len();
+                ui.label(format!("{count} colors"));
+                if let Some(budget) = app.palette.color_budget {
+                    if count > budget {
+                        ui.colored_label(
+                            Color32::from_rgb(230, 90, 80),
+                            format!("Over the color budget of {budget}"),
+                        );
+                    }
+                }
+            } else if state.format.is_gradient() {
+                ui.label(format!(
+                    "Gradient of {}",
+                    app.current_swatch().display_name(app.current_swatch_index)
+                ));
+                ui.add(
+                    Slider::new(&mut state.gradient_tolerance, 0.001..=0.05)
+                        .logarithmic(true)
+                        .text("Tolerance (ΔE)"),
+                );
+                if let Some((_, stops)) = gradient_export_stops(&source, state.format) {
+                    ui.label(format!("{} stops", stops.len()));
+                }
+            } else {
+                if state.format.has_channel_depth() {
+                    ui.horizontal(|ui| {
+                        ui.label("Channels:");
+                        for &depth in ChannelDepth::ALL {
+                            ui.selectable_value(&mut state.depth, depth, depth.name());
+                        }
+                    });
+                }
+                if state.format.is_shader() || (state.format.has_channel_depth() && state.depth != ChannelDepth::Eight) {
+                    ui.horizontal(|ui| {
+                        ui.label("Values:");
+                        ui.selectable_value(&mut state.linear, false, "sRGB");
+                        ui.selectable_value(&mut state.linear, true, "Linear");
+                    });
+                }
+                ui.label("Token names come from swatch names and the step naming scheme.");
+            }
+            // Shaders and float channels are written unquantized
+            let unquantized =
+                state.format.is_shader() || (state.format.has_channel_depth() && state.depth == ChannelDepth::Float);
+            if !unquantized {
+                egui::ComboBox::from_label("Rounding")
+                    .selected_text(state.rounding.name())
+                    .show_ui(ui, |ui| {
+                        for &rounding in Rounding::ALL {
+                            ui.selectable_value(&mut state.rounding, rounding, rounding.name());
+                        }
+                    });
+            }
+            let contents = export_palette(&source, state.format);
+
+            ScrollArea::vertical().max_height(PREVIEW_HEIGHT).show(ui, |ui| {
+                ui.monospace(&contents);
+            });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("File:");
+                ui.text_edit_singleline(&mut state.path);
+                if ui.button("Export").clicked() {
+                    let path = state.path.trim();
+                    state.status = Some(match std::fs::write(path, &contents) {
+                        Ok(()) => format!("Exported to {path}"),
+                        Err(err) => format!("Export failed: {err}"),
+                    });
+                }
+            });
+
+            if let Some(status) = &state.status {
+                ui.label(status);
+            }
+        });
+}
@@ -0,0 +1,255 @@
+use std::path::{Path, PathBuf};
+
+use egui_macroquad::egui::{self, Align2, Color32};
+
+use crate::app::App;
+use crate::settings::Settings;
+use crate::templates::PROJECT_EXTENSION;
+use crate::ui::new_palette::{draw_new_palette, NewPaletteState};
+
+/// Suggested name (without extension) for new project files
+const DEFAULT_PROJECT_NAME: &str = "palette";
+
+/// A File menu command. Commands that would discard unsaved changes ask first.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FileRequest {
+    New,
+    /// Open a project; None shows the path prompt
+    Open(Option<PathBuf>),
+    Save,
+    SaveAs,
+    Exit,
+}
+
+impl FileRequest {
+    fn discards_changes(&self) -> bool {
+        matches!(self, FileRequest::New | FileRequest::Open(_) | FileRequest::Exit)
+    }
+
+    /// Verb for the unsaved changes prompt
+    fn verb(&self) -> &'static str {
+        match self {
+            FileRequest::New => "starting a new palette",
+            FileRequest::Open(_) => "opening another palette",
+            _ => "exiting",
+        }
+    }
+}
+
+/// Which path prompt is open
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PathPrompt {
+    Open,
+    SaveAs,
+}
+
+/// UI state for the open/save prompts and the unsaved changes dialog
+#[derive(Default)]
+pub struct FileDialogState {
+    prompt: Option<PathPrompt>,
+    path: String,
+    /// A command that discards changes, waiting for the user's answer
+    confirm: Option<FileRequest>,
+    /// Command to run once the Save As prompt succeeds
+    after_save: Option<FileRequest>,
+    error: Option<String>,
+    /// The template chooser shown by New
+    new_palette: NewPaletteState,
+    new_palette_open: bool,
+    /// Set once Exit has been confirmed; the main loop then quits
+    pub quit: bool,
+}
+
+impl FileDialogState {
+    fn open_prompt(&mut self, prompt: PathPrompt, app: &App) {
+        self.prompt = Some(prompt);
+        self.error = None;
+        if prompt == PathPrompt::SaveAs || self.path.is_empty() {
+            self.path = app
+                .file_path
+                .as_ref()
+                .map_or_else(
+                    || format!("{DEFAULT_PROJECT_NAME}.{PROJECT_EXTENSION}"),
+                    |path| path.display().to_string(),
+                );
+        }
+    }
+
+    /// Start handling a request, asking first if it would discard changes.
+    /// Returns true if the palette was replaced.
+    fn request(&mut self, request: FileRequest, app: &mut App, settings: &mut Settings) -> bool {
+        if request.discards_changes() && app.dirty {
+            self.confirm = Some(request);
+            return false;
+        }
+        self.perform(request, app, settings)
+    }
+
+    fn perform(&mut self, request: FileRequest, app: &mut App, settings: &mut Settings) -> bool {
+        match request {
+            FileRequest::New => {
+                self.new_palette_open = true;
+                false
+            }
+            FileRequest::Open(None) => {
+                self.open_prompt(PathPrompt::Open, app);
+                false
+            }
+            FileRequest::Open(Some(path)) => match app.open_project(&path) {
+                Ok(()) => {
+                    settings.add_recent_file(&path);
+                    self.prompt = None;
+                    self.error = None;
+                    true
+                }
+                Err(err) => {
+                    self.error = Some(format!("Could not open {}: {err}", path.display()));
+                    false
+                }
+            },
+            FileRequest::Save => match app.file_path.clone() {
+                Some(path) => {
+                    self.save(&path, app, settings);
+                    false
+                }
+                None => {
+                    self.open_prompt(PathPrompt::SaveAs, app);
+                    false
+                }
+            },
+            FileRequest::SaveAs => {
+                self.open_prompt(PathPrompt::SaveAs, app);
+                false
+            }
+            FileRequest::Exit => {
+                self.quit = true;
+                false
+            }
+        }
+    }
+
+    fn save(&mut self, path: &Path, app: &mut App, settings: &mut Settings) -> bool {
+        match app.save_project(path) {
+            Ok(()) => {
+                settings.add_recent_file(path);
+                self.error = None;
+                true
+            }
+            Err(err) => {
+                self.error = Some(format!("Could not save {}: {err}", path.display()));
+                false
+            }
+        }
+    }
+}
+
+/// Handle a File menu request and draw the open/save prompts and the unsaved
+/// changes dialog. Returns true if the palette was replaced.
+pub fn draw_file_dialogs(
+    egui_ctx: &egui::Context,
+    app: &mut App,
+    state: &mut FileDialogState,
+    settings: &mut Settings,
+    request: &mut Option<FileRequest>,
+) -> bool {
+    let mut replaced = false;
+    if let Some(request) = request.take() {
+        replaced |= state.request(request, app, settings);
+    }
+
+    if let Some(pending) = state.confirm.clone() {
+        let mut answer: Option<bool> = None;
+        let mut cancel = false;
+        egui::Window::new("Unsaved Changes")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(egui_ctx, |ui| {
+                let name = app.title().trim_end_matches('*').to_string();
+                ui.label(format!("Save changes to {name} before {}?", pending.verb()));
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        answer = Some(true);
+                    }
+                    if ui.button("Don't Save").clicked() {
+                        answer = Some(false);
+                    }
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+
+        if cancel {
+            state.confirm = None;
+        } else if let Some(save) = answer {
+            state.confirm = None;
+            if !save {
+                replaced |= state.perform(pending, app, settings);
+            } else if let Some(path) = app.file_path.clone() {
+                if state.save(&path, app, settings) {
+                    replaced |= state.perform(pending, app, settings);
+                }
+            } else {
+                // Continue once the palette has a file
+                state.after_save = Some(pending);
+                state.open_prompt(PathPrompt::SaveAs, app);
+            }
+        }
+    }
+
+    replaced |= draw_new_palette(egui_ctx, app, &mut state.new_palette, &mut state.new_palette_open);
+
+    if let Some(prompt) = state.prompt {
+        let mut open = true;
+        let (title, button) = match prompt {
+            PathPrompt::Open => ("Open Palette", "Open"),
+            PathPrompt::SaveAs => ("Save Palette As", "Save"),
+        };
+        egui::Window::new(title)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(egui_ctx, |ui| {
+                let mut submit = false;
+                ui.horizontal(|ui| {
+                    ui.label("File:");
+                    let response = ui.text_edit_singleline(&mut state.path);
+                    submit = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    submit |= ui.button(button).clicked();
+                });
+                if let Some(error) = &state.error {
+                    ui.colored_label(Color32::from_rgb(230, 90, 80), error);
+                }
+                if !submit {
+                    return;
+                }
+
+                let path = PathBuf::from(state.path.trim());
+                match prompt {
+                    PathPrompt::Open => replaced |= state.perform(FileRequest::Open(Some(path)), app, settings),
+                    PathPrompt::SaveAs => {
+                        if state.save(&path, app, settings) {
+                            state.prompt = None;
+                            if let Some(next) = state.after_save.take() {
+                                replaced |= state.perform(next, app, settings);
+                            }
+                        }
+                    }
+                }
+            });
+        if !open {
+            state.prompt = None;
+            state.after_save = None;
+        }
+    } else if let Some(error) = &state.error {
+        // Errors from Save and recent files, which have no prompt to show them in
+        let mut open = true;
+        egui::Window::new("Error").open(&mut open).show(egui_ctx, |ui| {
+            ui.colored_label(Color32::from_rgb(230, 90, 80), error);
+        });
+        if !open {
+            state.error = None;
+        }
+    }
+
+    replaced
+}
@@ -0,0 +1,168 @@
+use egui_macroquad::egui::{self, Color32, TextureHandle};
+
+use crate::app::App;
+use crate::gradient_map::{gradient_map, GradientMapInput, GradientSource};
+use crate::image_io::SourceImage;
+use crate::ui::widgets::{draw_image_preview, load_image_texture};
+
+/// Largest width/height of each preview image
+const PREVIEW_MAX_SIZE: f32 = 256.0;
+
+/// UI state for the gradient map window
+pub struct GradientMapState {
+    /// Path of the grayscale or indexed sprite
+    pub path: String,
+    /// Path the recolored sprite is exported to
+    pub export_path: String,
+    pub source: GradientSource,
+    pub invert: bool,
+    input: Option<GradientMapInput>,
+    /// The sprite as loaded, for side-by-side comparison
+    original_texture: Option<TextureHandle>,
+    mapped: Option<SourceImage>,
+    mapped_texture: Option<TextureHandle>,
+    /// Swatch colors the current `mapped` image was computed with
+    mapped_ramp: Vec<Color32>,
+    /// Result of the last load/export
+    status: Option<String>,
+}
+
+impl Default for GradientMapState {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            export_path: "recolored.png".to_string(),
+            source: GradientSource::default(),
+            invert: false,
+            input: None,
+            original_texture: None,
+            mapped: None,
+            mapped_texture: None,
+            mapped_ramp: Vec::new(),
+            status: None,
+        }
+    }
+}
+
+impl GradientMapState {
+    fn load(&mut self, ctx: &egui::Context) {
+        let path = self.path.trim();
+        let loaded = GradientMapInput::load(path, self.source)
+            .and_then(|input| SourceImage::load(path).map(|image| (input, image)).map_err(|e| e.to_string()));
+        match loaded {
+            Ok((input, original)) => {
+                self.input = Some(input);
+                self.original_texture = Some(load_image_texture(ctx, "gradient_map_original", &original));
+                self.invalidate();
+                self.status = None;
+            }
+            Err(err) => self.status = Some(format!("Could not load sprite: {err}")),
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.mapped = None;
+        self.mapped_texture = None;
+    }
+
+    /// Recompute the recolored sprite if the swatch or the settings changed
+    fn update(&mut self, ramp: &[Color32]) {
+        if self.mapped_ramp != ramp {
+            self.mapped_ramp = ramp.to_vec();
+            self.invalidate();
+        }
+        if self.mapped.is_none() {
+            if let Some(input) = &self.input {
+                self.mapped = Some(gradient_map(input, ramp, self.invert));
+            }
+        }
+    }
+
+    fn export(&mut self) {
+        let Some(mapped) = &self.mapped else { return };
+        let path = self.export_path.trim();
+        self.status = Some(match mapped.save(path) {
+            Ok(()) => format!("Exported to {path}"),
+            Err(err) => format!("Export failed: {err}"),
+        });
+    }
+}
+
+/// Draw the gradient map window: a sprite recolored with the current swatch
+pub fn draw_gradient_map(
+    egui_ctx: &egui::Context,
+    app: &App,
+    state: &mut GradientMapState,
+    open: &mut bool,
+) {
+    egui::Window::new("Gradient Map")
+        .open(open)
+        .default_width(300.0)
+        .show(egui_ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Sprite:");
+                let response = ui.text_edit_singleline(&mut state.path);
+                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Load").clicked() || submitted {
+                    state.load(ui.ctx());
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Map from:");
+                let mut reload = false;
+                for &source in GradientSource::ALL {
+                    reload |= ui.selectable_value(&mut state.source, source, source.name()).changed();
+                }
+                // The level source decides how the file is decoded
+                if reload && state.input.is_some() {
+                    state.load(ui.ctx());
+                }
+            });
+            if ui.checkbox(&mut state.invert, "Invert").changed() {
+                state.invalidate();
+            }
+
+            if let Some(status) = &state.status {
+                ui.label(status);
+            }
+
+            let ramp = app
+                .generated_colors
+                .get(app.current_swatch_index)
+                .cloned()
+                .unwrap_or_default();
+            state.update(&ramp);
+
+            let (Some(original), Some(mapped)) = (&state.original_texture, &state.mapped) else {
+                ui.label("Load a grayscale or indexed PNG to recolor it with the selected swatch.");
+                return;
+            };
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.vertical(|ui| {
+                    ui.label("Sprite");
+                    draw_image_preview(ui, original, PREVIEW_MAX_SIZE);
+                });
+                ui.vertical(|ui| {
+                    ui.label(format!("Swatch {}", app.current_swatch_index + 1));
+                    let texture = state
+                        .mapped_texture
+                        .get_or_insert_with(|| load_image_texture(ui.ctx(), "gradient_map_result", mapped));
+                    draw_image_preview(ui, texture, PREVIEW_MAX_SIZE);
+                });
+            });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("Export to:");
+                ui.text_edit_singleline(&mut state.export_path);
+                if ui.button("Export PNG").clicked() {
+                    state.export();
+                }
+            });
+        });
+}
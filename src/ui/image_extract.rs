@@ -0,0 +1,196 @@
+use egui_macroquad::egui::{self, Color32, ProgressBar, Slider, TextureHandle, Vec2};
+
+use crate::app::App;
+use crate::color::ColorSpace;
+use crate::extract::{cluster_colors, group_into_ramps, ramps_to_swatches, Cluster, ClusterMethod};
+use crate::image_io::SourceImage;
+use crate::ui::widgets::{draw_color_bar, draw_color_swatch, draw_image_preview, load_image_texture};
+
+/// Largest width/height of the source image preview
+const PREVIEW_MAX_SIZE: f32 = 256.0;
+/// Size of the ramp preview bars
+const RAMP_PREVIEW_WIDTH: f32 = 160.0;
+const RAMP_PREVIEW_HEIGHT: f32 = 16.0;
+
+/// UI state for the image extraction window
+pub struct ImageExtractState {
+    /// Path typed by the user
+    pub path: String,
+    /// The loaded image, if any
+    source: Option<SourceImage>,
+    /// GPU texture for the preview (created lazily from `source`)
+    texture: Option<TextureHandle>,
+    pub method: ClusterMethod,
+    pub cluster_count: usize,
+    /// Largest hue gap in degrees within one ramp
+    pub hue_tolerance: f32,
+    /// Color space used by the generated swatches
+    pub color_space: ColorSpace,
+    /// Results of the last extraction
+    clusters: Vec<Cluster>,
+    ramps: Vec<Vec<Cluster>>,
+    /// Error from the last load attempt
+    error: Option<String>,
+}
+
+impl Default for ImageExtractState {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            source: None,
+            texture: None,
+            method: ClusterMethod::default(),
+            cluster_count: 16,
+            hue_tolerance: 30.0,
+            color_space: ColorSpace::OkLab,
+            clusters: Vec::new(),
+            ramps: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+impl ImageExtractState {
+    fn load(&mut self) {
+        match SourceImage::load(self.path.trim()) {
+            Ok(source) => {
+                self.source = Some(source);
+                self.texture = None;
+                self.error = None;
+                self.extract();
+            }
+            Err(err) => {
+                self.error = Some(format!("Could not load image: {err}"));
+            }
+        }
+    }
+
+    fn extract(&mut self) {
+        let Some(source) = &self.source else { return };
+        let pixels = source.sampled_pixels();
+        self.clusters = cluster_colors(&pixels, self.cluster_count, self.method);
+        self.regroup();
+    }
+
+    fn regroup(&mut self) {
+        self.ramps = group_into_ramps(&self.clusters, self.hue_tolerance);
+    }
+}
+
+/// Draw the "Extract from Image" window
+pub fn draw_image_extract(
+    egui_ctx: &egui::Context,
+    app: &mut App,
+    state: &mut ImageExtractState,
+    open: &mut bool,
+) {
+    egui::Window::new("Extract from Image")
+        .open(open)
+        .default_width(320.0)
+        .show(egui_ctx, |ui| {
+            // Source file
+            ui.horizontal(|ui| {
+                ui.label("File:");
+                let response = ui.text_edit_singleline(&mut state.path);
+                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Load").clicked() || submitted {
+                    state.load();
+                }
+            });
+
+            if let Some(error) = &state.error {
+                ui.colored_label(Color32::from_rgb(230, 90, 80), error);
+            }
+
+            let Some(source) = &state.source else {
+                ui.label("Load a PNG or JPEG to extract its dominant colors.");
+                return;
+            };
+
+            // Source preview
+            let texture = state
+                .texture
+                .get_or_insert_with(|| load_image_texture(ui.ctx(), "extract_source", source));
+            draw_image_preview(ui, texture, PREVIEW_MAX_SIZE);
+            ui.label(format!("{} × {} px", source.width, source.height));
+
+            ui.separator();
+
+            // Clustering parameters
+            let mut needs_extract = false;
+            egui::ComboBox::from_label("Method")
+                .selected_text(state.method.name())
+                .show_ui(ui, |ui| {
+                    for &method in ClusterMethod::ALL {
+                        needs_extract |= ui
+                            .selectable_value(&mut state.method, method, method.name())
+                            .changed();
+                    }
+                });
+            needs_extract |= ui
+                .add(Slider::new(&mut state.cluster_count, 2..=64).text("Colors"))
+                .changed();
+            if needs_extract {
+                state.extract();
+            }
+
+            if ui
+                .add(Slider::new(&mut state.hue_tolerance, 5.0..=90.0).text("Hue tolerance (°)"))
+                .changed()
+            {
+                state.regroup();
+            }
+
+            ui.separator();
+
+            // Cluster weights
+            ui.collapsing(format!("Dominant colors ({})", state.clusters.len()), |ui| {
+                for cluster in &state.clusters {
+                    ui.horizontal(|ui| {
+                        draw_color_swatch(ui, cluster.color, Vec2::new(24.0, 16.0));
+                        ui.add(
+                            ProgressBar::new(cluster.weight)
+                                .desired_width(160.0)
+                                .text(format!("{:.1}%", cluster.weight * 100.0)),
+                        );
+                    });
+                }
+            });
+
+            // Ramps
+            ui.label(format!("Ramps ({}):", state.ramps.len()));
+            for ramp in &state.ramps {
+                let colors: Vec<Color32> = ramp.iter().map(|c| c.color).collect();
+                ui.horizontal(|ui| {
+                    draw_color_bar(ui, &colors, RAMP_PREVIEW_WIDTH, RAMP_PREVIEW_HEIGHT);
+                    ui.label(format!("{} colors", colors.len()));
+                });
+            }
+
+            ui.separator();
+
+            // Swatch generation
+            ui.horizontal(|ui| {
+                ui.label("Swatch color space:");
+                egui::ComboBox::from_id_salt("extract_color_space")
+                    .selected_text(state.color_space.name())
+                    .show_ui(ui, |ui| {
+                        for &space in ColorSpace::ALL {
+                            ui.selectable_value(&mut state.color_space, space, space.name());
+                        }
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                let has_ramps = !state.ramps.is_empty();
+                if ui.add_enabled(has_ramps, egui::Button::new("Add as Swatches")).clicked() {
+                    for swatch in ramps_to_swatches(&state.ramps, state.color_space) {
+                        app.add_swatch(swatch);
+                    }
+                }
+                if ui.add_enabled(has_ramps, egui::Button::new("Replace Palette")).clicked() {
+                    app.replace_swatches(ramps_to_swatches(&state.ramps, state.color_space));
+                }
+            });
+        });
+}
@@ -0,0 +1,179 @@
+use egui_macroquad::egui::{self, DragValue, TextureHandle, Vec2};
+
+use crate::app::App;
+use crate::color::ColorSpace;
+use crate::image_io::SourceImage;
+use crate::lut::{cube_lut, ramp_atlas, ramp_texture, DEFAULT_LUT_SIZE, DEFAULT_RAMP_WIDTH, MAX_LUT_SIZE};
+use crate::ui::widgets::load_image_texture;
+
+/// Displayed size of the preview, independent of the texture resolution
+const PREVIEW_WIDTH: f32 = 256.0;
+const PREVIEW_ROW_HEIGHT: f32 = 16.0;
+
+/// Largest ramp texture width offered
+const MAX_RAMP_WIDTH: usize = 4096;
+
+/// What the lookup texture window exports
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LutKind {
+    /// The current swatch as a ramp PNG
+    #[default]
+    Ramp,
+    /// All swatches stacked into one PNG
+    Atlas,
+    /// A .cube 3D LUT that quantizes to the palette
+    Cube,
+}
+
+impl LutKind {
+    pub const ALL: &'static [LutKind] = &[LutKind::Ramp, LutKind::Atlas, LutKind::Cube];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LutKind::Ramp => "Swatch ramp",
+            LutKind::Atlas => "Ramp atlas",
+            LutKind::Cube => "3D LUT (.cube)",
+        }
+    }
+
+    fn default_path(&self) -> &'static str {
+        match self {
+            LutKind::Ramp => "ramp.png",
+            LutKind::Atlas => "ramp_atlas.png",
+            LutKind::Cube => "palette.cube",
+        }
+    }
+}
+
+/// UI state for the lookup texture window
+pub struct LutExportState {
+    pub kind: LutKind,
+    /// Ramp texture width in pixels
+    pub width: usize,
+    /// Height of each ramp row in pixels
+    pub row_height: usize,
+    /// Edge length of the 3D LUT
+    pub lut_size: usize,
+    /// Color space the 3D LUT matches palette colors in
+    pub color_space: ColorSpace,
+    pub path: String,
+    /// What the preview texture was rendered from: the app revision, kind,
+    /// width, row height and current swatch. It's only rendered again when
+    /// one of them changes.
+    rendered: Option<(u64, LutKind, usize, usize, usize)>,
+    texture: Option<TextureHandle>,
+    /// Result of the last export
+    status: Option<String>,
+}
+
+impl Default for LutExportState {
+    fn default() -> Self {
+        let kind = LutKind::default();
+        Self {
+            kind,
+            width: DEFAULT_RAMP_WIDTH,
+            row_height: 1,
+            lut_size: DEFAULT_LUT_SIZE,
+            color_space: ColorSpace::OkLab,
+            path: kind.default_path().to_string(),
+            rendered: None,
+            texture: None,
+            status: None,
+        }
+    }
+}
+
+impl LutExportState {
+    fn render(&self, app: &App) -> SourceImage {
+        match self.kind {
+            LutKind::Atlas => ramp_atlas(&app.palette, self.width, self.row_height),
+            _ => ramp_texture(&app.palette, app.current_swatch(), self.width, self.row_height),
+        }
+    }
+
+    fn export(&mut self, app: &App) {
+        let path = self.path.trim();
+        let result = match self.kind {
+            LutKind::Cube => {
+                let contents = cube_lut(&app.unique_colors(), self.lut_size, self.color_space, "Chrma Palette");
+                std::fs::write(path, contents).map_err(|err| err.to_string())
+            }
+            _ => self.render(app).save(path).map_err(|err| err.to_string()),
+        };
+        self.status = Some(match result {
+            Ok(()) => format!("Exported to {path}"),
+            Err(err) => format!("Export failed: {err}"),
+        });
+    }
+}
+
+/// Draw the lookup texture window: ramp PNGs and 3D LUTs for shaders
+pub fn draw_lut_export(egui_ctx: &egui::Context, app: &App, state: &mut LutExportState, open: &mut bool) {
+    egui::Window::new("Lookup Textures")
+        .open(open)
+        .default_width(300.0)
+        .show(egui_ctx, |ui| {
+            ui.horizontal(|ui| {
+                for &kind in LutKind::ALL {
+                    let previous = state.kind;
+                    if ui.selectable_value(&mut state.kind, kind, kind.name()).changed()
+                        && state.path.trim() == previous.default_path()
+                    {
+                        state.path = kind.default_path().to_string();
+                    }
+                }
+            });
+
+            ui.separator();
+
+            if state.kind == LutKind::Cube {
+                ui.horizontal(|ui| {
+                    ui.label("Size:");
+                    ui.add(DragValue::new(&mut state.lut_size).range(2..=MAX_LUT_SIZE));
+                    ui.label(format!("({} entries)", state.lut_size.pow(3)));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Match in:");
+                    egui::ComboBox::from_id_salt("lut_color_space")
+                        .selected_text(state.color_space.name())
+                        .show_ui(ui, |ui| {
+                            for &space in ColorSpace::ALL {
+                                ui.selectable_value(&mut state.color_space, space, space.name());
+                            }
+                        });
+                });
+                ui.label(format!("Quantizes to the {} unique palette colors.", app.unique_colors().len()));
+            } else {
+                ui.horizontal(|ui| {
+                    ui.label("Width:");
+                    ui.add(DragValue::new(&mut state.width).range(2..=MAX_RAMP_WIDTH));
+                    ui.label(if state.kind == LutKind::Atlas { "Row height:" } else { "Height:" });
+                    ui.add(DragValue::new(&mut state.row_height).range(1..=64));
+                });
+
+                let key = (app.revision, state.kind, state.width, state.row_height, app.current_swatch_index);
+                if state.rendered != Some(key) {
+                    state.texture = Some(load_image_texture(ui.ctx(), "lut_ramp", &state.render(app)));
+                    state.rendered = Some(key);
+                }
+                if let Some(texture) = &state.texture {
+                    let rows = if state.kind == LutKind::Atlas { app.swatch_count() } else { 1 };
+                    ui.image((texture.id(), Vec2::new(PREVIEW_WIDTH, PREVIEW_ROW_HEIGHT * rows as f32)));
+                }
+            }
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("Export to:");
+                ui.text_edit_singleline(&mut state.path);
+                if ui.button("Export").clicked() {
+                    state.export(app);
+                }
+            });
+
+            if let Some(status) = &state.status {
+                ui.label(status);
+            }
+        });
+}
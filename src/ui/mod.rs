@@ -1,10 +1,35 @@
 // UI modules for the palette helper application
 
+pub mod background_compare;
+pub mod canvas;
+pub mod command_palette;
+pub mod export_window;
+pub mod file_dialogs;
+pub mod gradient_map;
+pub mod image_extract;
+pub mod new_palette;
+pub mod lut_export;
 pub mod swatch_editor;
+pub mod swatch_fit;
 pub mod palette_editor;
+pub mod palette_preview;
+pub mod recovery;
+pub mod shortcuts;
 pub mod top_panel;
 pub mod widgets;
 
+pub use background_compare::draw_background_compare;
+pub use canvas::{draw_canvas_context_menu, handle_canvas_input, visible_canvas_area};
+pub use command_palette::draw_command_palette;
+pub use export_window::draw_export_window;
+pub use file_dialogs::draw_file_dialogs;
+pub use gradient_map::draw_gradient_map;
+pub use image_extract::draw_image_extract;
+pub use lut_export::draw_lut_export;
 pub use swatch_editor::draw_swatch_editor;
+pub use swatch_fit::draw_swatch_fit;
 pub use palette_editor::{draw_palette_editor, PaletteEditorState};
-pub use top_panel::draw_top_panel;
+pub use palette_preview::draw_palette_preview;
+pub use recovery::draw_recovery_prompt;
+pub use shortcuts::{draw_shortcuts_window, handle_shortcuts};
+pub use top_panel::{draw_top_panel, OpenWindows};
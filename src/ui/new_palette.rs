@@ -0,0 +1,105 @@
+use egui_macroquad::egui::{self, Color32, RichText};
+
+use crate::app::App;
+use crate::templates::{save_user_template, user_templates, BuiltinTemplate, UserTemplate};
+
+/// UI state for the New Palette window
+#[derive(Default)]
+pub struct NewPaletteState {
+    /// Saved templates, read when the window opens
+    user_templates: Option<Vec<UserTemplate>>,
+    /// Name for saving the current palette as a template
+    template_name: String,
+    status: Option<String>,
+}
+
+impl NewPaletteState {
+    fn refresh(&mut self) {
+        self.user_templates = Some(user_templates());
+    }
+}
+
+/// Draw the template chooser. Returns true if the palette was replaced, which
+/// also closes the window.
+pub fn draw_new_palette(egui_ctx: &egui::Context, app: &mut App, state: &mut NewPaletteState, open: &mut bool) -> bool {
+    if !*open {
+        // Re-read the templates folder next time
+        state.user_templates = None;
+        return false;
+    }
+    if state.user_templates.is_none() {
+        state.refresh();
+    }
+
+    let mut replaced = false;
+    egui::Window::new("New Palette")
+        .open(open)
+        .collapsible(false)
+        .default_width(300.0)
+        .show(egui_ctx, |ui| {
+            ui.label(RichText::new("Templates").strong());
+            for template in BuiltinTemplate::ALL {
+                if ui
+                    .button(template.name())
+                    .on_hover_text(template.description())
+                    .clicked()
+                {
+                    template.apply(app);
+                    replaced = true;
+                }
+            }
+
+            ui.separator();
+            ui.label(RichText::new("My Templates").strong());
+            let mut delete: Option<usize> = None;
+            let templates = state.user_templates.as_deref().unwrap_or_default();
+            if templates.is_empty() {
+                ui.label("No saved templates yet.");
+            }
+            for (i, template) in templates.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.button(&template.name).clicked() {
+                        match template.apply(app) {
+                            Ok(()) => replaced = true,
+                            Err(err) => state.status = Some(format!("Could not load template: {err}")),
+                        }
+                    }
+                    if ui.small_button("×").on_hover_text("Delete template").clicked() {
+                        delete = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = delete {
+                if let Some(template) = state.user_templates.as_ref().and_then(|t| t.get(i)) {
+                    if let Err(err) = template.delete() {
+                        state.status = Some(format!("Could not delete template: {err}"));
+                    }
+                }
+                state.refresh();
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Save current as:");
+                ui.text_edit_singleline(&mut state.template_name);
+                let name = state.template_name.trim().to_string();
+                if ui.add_enabled(!name.is_empty(), egui::Button::new("Save")).clicked() {
+                    state.status = Some(match save_user_template(&name, app) {
+                        Ok(()) => format!("Saved template \"{name}\""),
+                        Err(err) => format!("Could not save template: {err}"),
+                    });
+                    state.refresh();
+                }
+            });
+
+            if let Some(status) = &state.status {
+                ui.colored_label(Color32::GRAY, status);
+            }
+        });
+
+    if replaced {
+        *open = false;
+        state.status = None;
+    }
+    replaced
+}
@@ -1,7 +1,9 @@
 use egui_macroquad::egui::{self, Color32};
 
 use crate::app::App;
-use crate::palette::Swatch;
+use crate::export::slugify;
+use crate::gamut::{GamutMapping, OutputSpace};
+use crate::palette::{NamedColor, StepNaming, Swatch};
 use crate::ui::widgets::{draggable_list_item, draw_color_bar, sample_colors, DragDropResult, DragDropState};
 
 /// Number of sample colors to show in the swatch preview
@@ -9,12 +11,19 @@ const PREVIEW_SAMPLES: usize = 4;
 /// Size of the preview rectangle
 const PREVIEW_WIDTH: f32 = 100.0;
 const PREVIEW_HEIGHT: f32 = 20.0;
+/// Common color budgets for pixel-art palettes
+const BUDGET_PRESETS: &[usize] = &[8, 16, 32, 64];
 
 /// UI state for the palette editor
 #[derive(Default)]
 pub struct PaletteEditorState {
     /// State for drag-drop reordering
     pub drag_state: DragDropState,
+    /// Custom step names as typed, applied when the field loses focus
+    step_names_text: Option<String>,
+    /// A named color being renamed and its name as typed, applied when the
+    /// field loses focus
+    named_color_edit: Option<(usize, String)>,
 }
 
 /// Actions to perform on swatches
@@ -56,7 +65,7 @@ pub fn draw_palette_editor(egui_ctx: &egui::Context, app: &mut App, state: &mut
                         draw_color_bar(ui, &colors, PREVIEW_WIDTH, PREVIEW_HEIGHT);
 
                         // Swatch info
-                        ui.label(format!("{} colors", swatch.size));
+                        ui.label(format!("{} ({})", swatch.display_name(i), swatch.size));
 
                         // Select button (shown for all, but styled differently if selected)
                         ui.add_enabled_ui(!is_selected, |ui| {
@@ -95,5 +104,205 @@ pub fn draw_palette_editor(egui_ctx: &egui::Context, app: &mut App, state: &mut
             if ui.button("+ Add Swatch").clicked() {
                 app.add_swatch(Swatch::default());
             }
+
+            ui.separator();
+
+            draw_named_colors_section(ui, app, &mut state.named_color_edit);
+            draw_step_naming_section(ui, app, &mut state.step_names_text);
+            draw_constraints_section(ui, app);
+            draw_output_section(ui, app);
         });
 }
+
+/// Palette-level named colors that control points can link to
+fn draw_named_colors_section(ui: &mut egui::Ui, app: &mut App, name_edit: &mut Option<(usize, String)>) {
+    ui.collapsing("Named Colors", |ui| {
+        let mut changed = false;
+        let mut remove: Option<usize> = None;
+
+        for i in 0..app.palette.named_colors.len() {
+            ui.horizontal(|ui| {
+                // Links follow a rename, so it's applied once editing ends
+                // rather than through every intermediate name
+                let mut name = match name_edit {
+                    Some((index, text)) if *index == i => text.clone(),
+                    _ => app.palette.named_colors[i].name.clone(),
+                };
+                let response = ui.add(egui::TextEdit::singleline(&mut name).desired_width(100.0));
+                if response.has_focus() {
+                    *name_edit = Some((i, name));
+                } else if response.lost_focus() {
+                    if name != app.palette.named_colors[i].name && app.palette.rename_named_color(i, &name) {
+                        app.mark_dirty();
+                    }
+                    *name_edit = None;
+                }
+                response.on_hover_text("Names must be unique and not empty; other names are rejected");
+                // Only an edit replaces the stored color with the picker's 8-bit one
+                let mut color = Color32::from(app.palette.named_colors[i].color);
+                if ui.color_edit_button_srgba(&mut color).changed() {
+                    app.palette.named_colors[i].color = color.into();
+                    changed = true;
+                }
+                if ui.button("×").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+
+        if let Some(i) = remove {
+            // Links to the removed color become broken and keep their last color
+            app.palette.named_colors.remove(i);
+            *name_edit = None;
+            app.mark_dirty();
+        }
+
+        if ui.button("+ Add Named Color").clicked() {
+            let name = app.palette.unused_color_name();
+            app.palette.named_colors.push(NamedColor {
+                name,
+                color: Color32::GRAY.into(),
+            });
+            app.mark_dirty();
+        }
+
+        if changed {
+            app.regenerate_all_colors();
+            app.mark_dirty();
+        }
+    });
+}
+
+/// Naming scheme for swatch steps, used by token exports
+fn draw_step_naming_section(ui: &mut egui::Ui, app: &mut App, edit_text: &mut Option<String>) {
+    ui.collapsing("Step Naming", |ui| {
+        let size = app.current_swatch().size;
+        egui::ComboBox::from_label("Scheme")
+            .selected_text(app.palette.step_naming.name())
+            .show_ui(ui, |ui| {
+                for (label, kind) in StepNaming::KINDS {
+                    let selected = app.palette.step_naming.same_kind(kind);
+                    if ui.selectable_label(selected, *label).clicked() && !selected {
+                        app.palette.step_naming = match kind {
+                            // Start custom names from whatever the previous scheme produced
+                            StepNaming::Custom(_) => StepNaming::Custom(app.palette.step_naming.step_names(size)),
+                            other => other.clone(),
+                        };
+                        app.mark_dirty();
+                    }
+                }
+            });
+
+        if let StepNaming::Custom(names) = &mut app.palette.step_naming {
+            ui.label("Names, comma separated (bright to dark):");
+            // Splitting on every keystroke would trim away a space as soon as
+            // it's typed, so the names are only parsed once editing ends
+            let text = edit_text.get_or_insert_with(|| names.join(", "));
+            let response = ui.text_edit_singleline(text);
+            if response.lost_focus() {
+                let edited: Vec<String> = text.split(',').map(|name| name.trim().to_string()).collect();
+                if edited != *names {
+                    *names = edited;
+                    app.mark_dirty();
+                }
+                *edit_text = None;
+            } else if !response.has_focus() {
+                *edit_text = None;
+            }
+        }
+
+        // Preview with the current swatch
+        let swatch_name = slugify(&app.current_swatch().display_name(app.current_swatch_index));
+        let examples: Vec<String> = app
+            .palette
+            .step_naming
+            .step_names(size)
+            .iter()
+            .map(|step| format!("{swatch_name}-{}", slugify(step)))
+            .collect();
+        ui.label(examples.join(", "));
+    });
+}
+
+/// Color budget and duplicate merging settings, with a live color counter
+fn draw_constraints_section(ui: &mut egui::Ui, app: &mut App) {
+    ui.collapsing("Constraints", |ui| {
+        let mut has_budget = app.palette.color_budget.is_some();
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut has_budget, "Color budget").changed() {
+                app.palette.color_budget = has_budget.then_some(BUDGET_PRESETS[1]);
+                changed = true;
+            }
+            if let Some(budget) = &mut app.palette.color_budget {
+                changed |= ui.add(egui::DragValue::new(budget).range(1..=256)).changed();
+                for &preset in BUDGET_PRESETS {
+                    changed |= ui.selectable_value(budget, preset, preset.to_string()).changed();
+                }
+            }
+        });
+
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut app.palette.merge_threshold, 0.0..=0.1)
+                    .text("Merge ΔE")
+                    .fixed_decimals(3),
+            )
+            .on_hover_text("Colors closer than this (in OkLab) count as one color. 0 merges only identical colors.")
+            .changed();
+        if changed {
+            app.mark_dirty();
+        }
+
+        let total = app.flattened_colors().len();
+        let unique = app.unique_colors().len();
+        let counter = match app.palette.color_budget {
+            Some(budget) => format!("{unique} / {budget} colors"),
+            None => format!("{unique} colors"),
+        };
+        if app.is_over_budget() {
+            ui.colored_label(Color32::from_rgb(230, 90, 80), format!("{counter} (over budget)"));
+        } else {
+            ui.label(counter);
+        }
+        if unique < total {
+            ui.label(format!("{} duplicates merged from {total} generated", total - unique));
+        }
+    });
+}
+
+/// The color space generated colors are mapped into and exported in
+fn draw_output_section(ui: &mut egui::Ui, app: &mut App) {
+    ui.collapsing("Output", |ui| {
+        let mut changed = false;
+        egui::ComboBox::from_label("Color space")
+            .selected_text(app.palette.output_space.name())
+            .show_ui(ui, |ui| {
+                for &space in OutputSpace::ALL {
+                    changed |= ui
+                        .selectable_value(&mut app.palette.output_space, space, space.name())
+                        .changed();
+                }
+            });
+        egui::ComboBox::from_label("Gamut mapping")
+            .selected_text(app.palette.gamut_mapping.name())
+            .show_ui(ui, |ui| {
+                for &mapping in GamutMapping::ALL {
+                    changed |= ui
+                        .selectable_value(&mut app.palette.gamut_mapping, mapping, mapping.name())
+                        .changed();
+                }
+            });
+        if changed {
+            app.regenerate_all_colors();
+            app.mark_dirty();
+        }
+
+        if app.palette.output_space != OutputSpace::Srgb {
+            let outside = (0..app.palette.swatches.len())
+                .map(|index| app.palette.outside_srgb(index).into_iter().filter(|&flag| flag).count())
+                .sum::<usize>();
+            ui.label(format!("{outside} colors beyond sRGB, marked in the corner and shown clipped"));
+        }
+    });
+}
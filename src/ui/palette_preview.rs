@@ -0,0 +1,224 @@
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+use egui_macroquad::egui::{self, Color32, Slider, TextureHandle};
+
+use crate::app::App;
+use crate::color::ColorSpace;
+use crate::image_io::SourceImage;
+use crate::mapping::{remap_image, Dither};
+use crate::ui::widgets::{draw_image_preview, load_image_texture};
+
+/// Largest width/height of each preview image
+const PREVIEW_MAX_SIZE: f32 = 320.0;
+
+/// UI state for the palette preview window
+pub struct PalettePreviewState {
+    /// Path of the sample image
+    pub path: String,
+    /// Path the remapped image is exported to
+    pub export_path: String,
+    pub color_space: ColorSpace,
+    pub dither: Dither,
+    /// Dithering strength in [0.0, 1.0]
+    pub strength: f32,
+    source: Option<Arc<SourceImage>>,
+    source_texture: Option<TextureHandle>,
+    /// The last remapped image, shown until a newer one is ready
+    remapped: Option<SourceImage>,
+    remapped_texture: Option<TextureHandle>,
+    /// Palette the latest remap was started with
+    mapped_palette: Vec<Color32>,
+    /// The palette or settings changed since the latest remap was started
+    stale: bool,
+    /// The remap running on a worker thread, so editing colors stays smooth
+    remapping: Option<Receiver<SourceImage>>,
+    /// Result of the last load/export
+    status: Option<String>,
+}
+
+impl Default for PalettePreviewState {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            export_path: "remapped.png".to_string(),
+            color_space: ColorSpace::OkLab,
+            dither: Dither::default(),
+            strength: 1.0,
+            source: None,
+            source_texture: None,
+            remapped: None,
+            remapped_texture: None,
+            mapped_palette: Vec::new(),
+            stale: false,
+            remapping: None,
+            status: None,
+        }
+    }
+}
+
+impl PalettePreviewState {
+    fn load(&mut self) {
+        match SourceImage::load(self.path.trim()) {
+            Ok(source) => {
+                self.source = Some(Arc::new(source));
+                self.source_texture = None;
+                // The old image's remap is of no use any more
+                self.remapped = None;
+                self.remapped_texture = None;
+                self.remapping = None;
+                self.invalidate();
+                self.status = None;
+            }
+            Err(err) => self.status = Some(format!("Could not load image: {err}")),
+        }
+    }
+
+    /// Remap the image again once the running remap is done
+    fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// Pick up a finished remap, and start a new one if the settings or the
+    /// palette changed. Only one remap runs at a time; changes made while it
+    /// runs are picked up by the next.
+    fn update(&mut self, egui_ctx: &egui::Context, palette: &[Color32]) {
+        if self.mapped_palette != palette {
+            self.mapped_palette = palette.to_vec();
+            self.invalidate();
+        }
+        if let Some(receiver) = &self.remapping {
+            match receiver.try_recv() {
+                Ok(remapped) => {
+                    self.remapped = Some(remapped);
+                    self.remapped_texture = None;
+                    self.remapping = None;
+                }
+                Err(TryRecvError::Empty) => {
+                    egui_ctx.request_repaint();
+                    return;
+                }
+                Err(TryRecvError::Disconnected) => self.remapping = None,
+            }
+        }
+        let Some(source) = self.source.as_ref().filter(|_| self.stale) else { return };
+        let (source, palette) = (Arc::clone(source), palette.to_vec());
+        let (space, dither, strength) = (self.color_space, self.dither, self.strength);
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(remap_image(&source, &palette, space, dither, strength));
+        });
+        self.remapping = Some(receiver);
+        self.stale = false;
+        egui_ctx.request_repaint();
+    }
+
+    fn export(&mut self) {
+        let Some(remapped) = &self.remapped else { return };
+        let path = self.export_path.trim();
+        self.status = Some(match remapped.save(path) {
+            Ok(()) => format!("Exported to {path}"),
+            Err(err) => format!("Export failed: {err}"),
+        });
+    }
+}
+
+/// Draw the palette preview window: a sample image remapped to the palette
+pub fn draw_palette_preview(
+    egui_ctx: &egui::Context,
+    app: &App,
+    state: &mut PalettePreviewState,
+    open: &mut bool,
+) {
+    egui::Window::new("Palette Preview")
+        .open(open)
+        .default_width(360.0)
+        .show(egui_ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Image:");
+                let response = ui.text_edit_singleline(&mut state.path);
+                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Load").clicked() || submitted {
+                    state.load();
+                }
+            });
+
+            // Mapping settings
+            let mut changed = false;
+            ui.horizontal(|ui| {
+                ui.label("Match in:");
+                egui::ComboBox::from_id_salt("preview_color_space")
+                    .selected_text(state.color_space.name())
+                    .show_ui(ui, |ui| {
+                        for &space in ColorSpace::ALL {
+                            changed |= ui.selectable_value(&mut state.color_space, space, space.name()).changed();
+                        }
+                    });
+            });
+            egui::ComboBox::from_label("Dithering")
+                .selected_text(state.dither.name())
+                .show_ui(ui, |ui| {
+                    for &dither in Dither::ALL {
+                        changed |= ui.selectable_value(&mut state.dither, dither, dither.name()).changed();
+                    }
+                });
+            ui.add_enabled_ui(state.dither != Dither::None, |ui| {
+                changed |= ui
+                    .add(Slider::new(&mut state.strength, 0.0..=1.0).text("Strength"))
+                    .changed();
+            });
+            if changed {
+                state.invalidate();
+            }
+
+            if let Some(status) = &state.status {
+                ui.label(status);
+            }
+
+            // Duplicates can't change the result, only slow the matching down
+            let palette = app.unique_colors();
+            state.update(egui_ctx, &palette);
+
+            let (Some(source), Some(remapped)) = (&state.source, &state.remapped) else {
+                ui.label("Load a sample image to see it in the current palette.");
+                return;
+            };
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.vertical(|ui| {
+                    ui.label("Original");
+                    let texture = state
+                        .source_texture
+                        .get_or_insert_with(|| load_image_texture(ui.ctx(), "preview_source", source));
+                    draw_image_preview(ui, texture, PREVIEW_MAX_SIZE);
+                });
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Remapped ({} colors)", palette.len()));
+                        if state.remapping.is_some() {
+                            ui.spinner();
+                        }
+                    });
+                    let texture = state
+                        .remapped_texture
+                        .get_or_insert_with(|| load_image_texture(ui.ctx(), "preview_remapped", remapped));
+                    draw_image_preview(ui, texture, PREVIEW_MAX_SIZE);
+                });
+            });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("Export to:");
+                ui.text_edit_singleline(&mut state.export_path);
+                // Export once the image shows the current palette
+                let current = state.remapping.is_none() && !state.stale;
+                if ui.add_enabled(current, egui::Button::new("Export PNG")).clicked() {
+                    state.export();
+                }
+            });
+        });
+}
@@ -0,0 +1,39 @@
+use egui_macroquad::egui::{self, Align2};
+
+use crate::app::App;
+use crate::project::Project;
+
+/// Draw the prompt offering to restore the session that exited uncleanly.
+/// Returns true if the project was restored.
+pub fn draw_recovery_prompt(egui_ctx: &egui::Context, app: &mut App, recovered: &mut Option<Project>) -> bool {
+    let Some(project) = recovered else { return false };
+    let mut restore = false;
+    let mut discard = false;
+
+    egui::Window::new("Restore Session")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(egui_ctx, |ui| {
+            ui.label("The last session did not exit cleanly.");
+            ui.label(format!(
+                "Restore its autosaved palette ({} swatches)?",
+                project.palette.swatches.len()
+            ));
+            ui.horizontal(|ui| {
+                restore = ui.button("Restore").clicked();
+                discard = ui.button("Discard").clicked();
+            });
+        });
+
+    if restore {
+        if let Some(project) = recovered.take() {
+            project.apply(app);
+            // The restored palette was never saved
+            app.mark_dirty();
+        }
+    } else if discard {
+        *recovered = None;
+    }
+    restore
+}
@@ -0,0 +1,170 @@
+use egui_macroquad::egui::{self, Color32, Grid, ScrollArea};
+
+use crate::app::App;
+use crate::clipboard::{self, ColorFormat};
+use crate::keymap::{shortcut_text, Action, Keymap};
+use crate::palette::Swatch;
+use crate::ui::file_dialogs::FileRequest;
+use crate::ui::swatch_editor::SwatchEditorState;
+use crate::ui::top_panel::OpenWindows;
+
+/// Zoom factor of one Zoom In step (Zoom Out is the inverse)
+const ZOOM_STEP: f32 = 1.25;
+
+/// Shown when the clipboard held no color, color list, swatch or palette
+const NOTHING_TO_PASTE: &str = "Nothing to paste: the clipboard holds no colors or valid swatch";
+
+/// Run an editor action
+pub fn perform_action(action: Action, app: &mut App, swatch_state: &mut SwatchEditorState, windows: &mut OpenWindows) {
+    match action {
+        Action::SelectPreviousSwatch => app.select_swatch(app.current_swatch_index.saturating_sub(1)),
+        Action::SelectNextSwatch => app.select_swatch(app.current_swatch_index + 1),
+        Action::SelectPreviousColor => app.select_color(app.selected_color_index.saturating_sub(1)),
+        Action::SelectNextColor => app.select_color(app.selected_color_index + 1),
+        Action::AddSwatch => {
+            app.add_swatch(Swatch::default());
+            app.select_swatch(app.swatch_count() - 1);
+        }
+        Action::DuplicateSwatch => {
+            let index = app.current_swatch_index;
+            app.duplicate_swatch(index);
+            app.select_swatch(index + 1);
+        }
+        Action::RemoveSwatch => app.remove_swatch(app.current_swatch_index),
+        Action::MoveSwatchUp => app.move_swatch_up(app.current_swatch_index),
+        Action::MoveSwatchDown => app.move_swatch_down(app.current_swatch_index),
+        Action::AddControlPoint => {
+            let index = app.selected_color_index;
+            if let Some(&color) = app.generated_colors.get(app.current_swatch_index).and_then(|c| c.get(index)) {
+                app.pin_color(index, color);
+            }
+        }
+        Action::RemoveControlPoint => app.unpin_color(app.selected_color_index),
+        Action::PinColor => swatch_state.pin_selected_color(app),
+        Action::ToggleStepLock => swatch_state.toggle_step_lock(app, app.selected_color_index),
+        Action::CycleColorSpace => app.cycle_color_space(),
+        Action::CycleCurve => app.cycle_curve(),
+        Action::CopyColorHex => clipboard::copy_selected_color(app, ColorFormat::Hex),
+        Action::CopyColorRgb => clipboard::copy_selected_color(app, ColorFormat::Rgb),
+        Action::CopyColorOklch => clipboard::copy_selected_color(app, ColorFormat::Oklch),
+        Action::CopySwatchColors => clipboard::copy_swatch_colors(app),
+        Action::CopySwatch => clipboard::copy_swatch(app),
+        Action::CopyPalette => clipboard::copy_palette(app),
+        Action::Paste => {
+            if !clipboard::paste(app) {
+                windows.notice = Some(NOTHING_TO_PASTE.to_string());
+            }
+        }
+        Action::ZoomIn => app.viewport.zoom_by(ZOOM_STEP),
+        Action::ZoomOut => app.viewport.zoom_by(1.0 / ZOOM_STEP),
+        Action::ResetView => app.viewport.reset(),
+        Action::ZoomToFit => app.zoom_to_fit(),
+        Action::FrameSelectedSwatch => app.frame_current_swatch(),
+        Action::CycleLayout => app.cycle_layout_mode(),
+        Action::CompareBackgrounds => windows.background_compare = true,
+        Action::NewPalette => windows.file_request = Some(FileRequest::New),
+        Action::OpenPalette => windows.file_request = Some(FileRequest::Open(None)),
+        Action::SavePalette => windows.file_request = Some(FileRequest::Save),
+        Action::SavePaletteAs => windows.file_request = Some(FileRequest::SaveAs),
+        Action::Export => windows.export = true,
+        Action::ExportLookupTextures => windows.lut_export = true,
+        Action::FitSwatch => windows.swatch_fit = true,
+        Action::ExtractFromImage => windows.image_extract = true,
+        Action::PreviewOnImage => windows.palette_preview = true,
+        Action::GradientMapSprite => windows.gradient_map = true,
+        Action::ShowShortcuts => windows.shortcuts = true,
+        Action::CommandPalette => windows.command_palette = true,
+        Action::Exit => windows.file_request = Some(FileRequest::Exit),
+    }
+    // Curve controls follow the (possibly different) current swatch
+    swatch_state.sync_with_swatch(app);
+}
+
+/// Run the action chosen from a menu, and those whose shortcuts were pressed
+/// this frame. Shortcuts are ignored while a text field has keyboard focus.
+pub fn handle_shortcuts(
+    egui_ctx: &egui::Context,
+    keymap: &Keymap,
+    app: &mut App,
+    swatch_state: &mut SwatchEditorState,
+    windows: &mut OpenWindows,
+) {
+    if let Some(action) = windows.action.take() {
+        perform_action(action, app, swatch_state, windows);
+    }
+    if egui_ctx.wants_keyboard_input() {
+        return;
+    }
+    // Some backends deliver Ctrl+C and Ctrl+V as clipboard events rather than keys
+    let (copied, pasted) = egui_ctx.input(|input| {
+        let copied = input.events.iter().any(|event| matches!(event, egui::Event::Copy));
+        let pasted = input.events.iter().find_map(|event| match event {
+            egui::Event::Paste(text) => Some(text.clone()),
+            _ => None,
+        });
+        (copied, pasted)
+    });
+    let mut pressed: Vec<Action> = egui_ctx.input_mut(|input| {
+        keymap
+            .bindings_by_specificity()
+            .into_iter()
+            .filter(|(_, shortcut)| input.consume_shortcut(shortcut))
+            .map(|(action, _)| action)
+            .collect()
+    });
+    if copied && pressed.is_empty() {
+        pressed.push(Action::CopyColorHex);
+    }
+    if let Some(text) = pasted {
+        pressed.retain(|&action| action != Action::Paste);
+        if !clipboard::paste_text(app, &text) {
+            windows.notice = Some(NOTHING_TO_PASTE.to_string());
+        }
+        swatch_state.sync_with_swatch(app);
+    }
+    for action in pressed {
+        perform_action(action, app, swatch_state, windows);
+    }
+}
+
+/// Draw the list of shortcuts, with controls for the keymap file
+pub fn draw_shortcuts_window(egui_ctx: &egui::Context, keymap: &mut Keymap, open: &mut bool) {
+    egui::Window::new("Keyboard Shortcuts")
+        .open(open)
+        .default_width(340.0)
+        .show(egui_ctx, |ui| {
+            ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                Grid::new("shortcuts_grid").striped(true).show(ui, |ui| {
+                    for &action in Action::ALL {
+                        ui.label(action.name());
+                        let shortcuts: Vec<String> = keymap.shortcuts(action).iter().map(shortcut_text).collect();
+                        if shortcuts.is_empty() {
+                            ui.weak("unbound");
+                        } else {
+                            ui.monospace(shortcuts.join(", "));
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+
+            ui.separator();
+
+            if let Some(path) = Keymap::path() {
+                ui.label(format!("Keymap file: {}", path.display()));
+                ui.horizontal(|ui| {
+                    if ui.button("Reload").clicked() {
+                        *keymap = Keymap::load();
+                    }
+                    if !path.exists() && ui.button("Create From Current").clicked() {
+                        if let Err(err) = keymap.save() {
+                            keymap.errors = vec![format!("Could not write keymap: {err}")];
+                        }
+                    }
+                });
+            }
+            for error in &keymap.errors {
+                ui.colored_label(Color32::from_rgb(230, 90, 80), error);
+            }
+        });
+}
@@ -1,9 +1,12 @@
+use egui_macroquad::egui::color_picker::{color_edit_button_srgba, Alpha};
 use egui_macroquad::egui::{self, Color32, Slider, Vec2};
 
 use crate::app::App;
 use crate::color::ColorSpace;
 use crate::curves::{CurveKind, CurveType, EaseIn, EaseInOut, EaseOut, Linear};
-use crate::ui::widgets::{draggable_list_item, draw_color_swatch, DragDropResult, DragDropState};
+use crate::export::hex;
+use crate::palette::{ColorLink, StepAlias, StepResize, MAX_SWATCH_SIZE, MIN_SWATCH_SIZE};
+use crate::ui::widgets::{draggable_list_item, draw_color_bar, draw_color_swatch, DragDropResult, DragDropState};
 
 // =============================================================================
 // HexEditState: Tracks color edits in the generated palette
@@ -70,6 +73,34 @@ impl HexEditState {
 // SwatchEditorState
 // =============================================================================
 
+/// Resize operation offered by the resize section, relative to the selected step
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ResizeKind {
+    InsertBefore,
+    #[default]
+    InsertAfter,
+    Remove,
+    Resample,
+}
+
+impl ResizeKind {
+    pub const ALL: &'static [ResizeKind] = &[
+        ResizeKind::InsertBefore,
+        ResizeKind::InsertAfter,
+        ResizeKind::Remove,
+        ResizeKind::Resample,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResizeKind::InsertBefore => "Insert before",
+            ResizeKind::InsertAfter => "Insert after",
+            ResizeKind::Remove => "Remove",
+            ResizeKind::Resample => "Resample",
+        }
+    }
+}
+
 /// UI state for the swatch editor
 pub struct SwatchEditorState {
     pub selected_curve_kind: CurveKind,
@@ -79,6 +110,11 @@ pub struct SwatchEditorState {
     pub hex_edit_state: HexEditState,
     /// State for drag-drop reordering of control points
     pub control_point_drag_state: DragDropState,
+    /// Why the last link attempt was rejected
+    pub link_error: Option<String>,
+    pub resize_kind: ResizeKind,
+    /// Step count for `ResizeKind::Resample`
+    pub resample_size: usize,
 }
 
 impl Default for SwatchEditorState {
@@ -89,6 +125,9 @@ impl Default for SwatchEditorState {
             linear_factor: 1.0,
             hex_edit_state: HexEditState::default(),
             control_point_drag_state: DragDropState::default(),
+            link_error: None,
+            resize_kind: ResizeKind::default(),
+            resample_size: 8,
         }
     }
 }
@@ -107,6 +146,22 @@ impl SwatchEditorState {
             CurveType::Bezier(_) => {}
         }
     }
+
+    /// Pin the selected color as a control point, if it was edited
+    pub fn pin_selected_color(&mut self, app: &mut App) {
+        let index = app.selected_color_index;
+        if let (true, Some(color)) = (self.hex_edit_state.was_edited(index), self.hex_edit_state.get(index)) {
+            self.hex_edit_state.clear_edit(index);
+            app.pin_color(index, color);
+        }
+    }
+
+    /// Lock a step at its edited color, if it was edited, or unlock it
+    pub fn toggle_step_lock(&mut self, app: &mut App, index: usize) {
+        let edited = self.hex_edit_state.get(index).filter(|_| self.hex_edit_state.was_edited(index));
+        self.hex_edit_state.clear_edit(index);
+        app.toggle_step_lock(index, edited);
+    }
 }
 
 // =============================================================================
@@ -116,12 +171,14 @@ impl SwatchEditorState {
 enum ControlPointAction {
     Swap(u32, u32),
     Remove(u32),
+    Link(u32, Option<ColorLink>),
 }
 
 enum ColorAction {
     SetColor(usize, Color32),
     Pin(usize),
     Revert(usize),
+    ToggleLock(usize),
 }
 
 // =============================================================================
@@ -140,15 +197,29 @@ pub fn draw_swatch_editor(
     }
 
     egui::Window::new("Swatch Editor").show(egui_ctx, |ui| {
+        // Swatch name (used for design tokens)
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            let index = app.current_swatch_index;
+            let hint = app.current_swatch().display_name(index);
+            if ui
+                .add(egui::TextEdit::singleline(&mut app.current_swatch_mut().name).hint_text(hint))
+                .changed()
+            {
+                app.mark_dirty();
+            }
+        });
+
         // Swatch size control
         let mut size = app.current_swatch().size;
         if ui
-            .add(Slider::new(&mut size, 2..=32).text("Swatch size"))
+            .add(Slider::new(&mut size, MIN_SWATCH_SIZE..=MAX_SWATCH_SIZE).text("Swatch size"))
             .changed()
         {
-            app.current_swatch_mut().size = size;
+            app.current_swatch_mut().set_size(size);
             app.regenerate_current_colors();
         }
+        draw_resize_section(ui, app, state);
 
         ui.separator();
 
@@ -184,11 +255,128 @@ pub fn draw_swatch_editor(
         // Interpolation curve section
         ui.label("Interpolation Curve:");
         draw_curve_editor(ui, app, state);
+        draw_alpha_curve_editor(ui, app);
 
         ui.separator();
 
         // Editable color values section
         draw_color_values_section(ui, app, state);
+
+        draw_step_aliases_section(ui, app);
+    });
+}
+
+/// Width of the before/after bars of the resize section
+const RESIZE_PREVIEW_WIDTH: f32 = 200.0;
+const RESIZE_PREVIEW_HEIGHT: f32 = 16.0;
+
+/// Insert, remove or resample steps. Unlike the size slider, which stretches
+/// the ramp, these keep pinned, locked and edited colors on their steps.
+fn draw_resize_section(ui: &mut egui::Ui, app: &mut App, state: &mut SwatchEditorState) {
+    ui.collapsing("Resize Steps", |ui| {
+        ui.label("Pinned, locked and edited colors stay on their steps.");
+        ui.horizontal(|ui| {
+            for &kind in ResizeKind::ALL {
+                ui.selectable_value(&mut state.resize_kind, kind, kind.name());
+            }
+        });
+
+        let selected = app.selected_color_index;
+        let resize = match state.resize_kind {
+            ResizeKind::InsertBefore => StepResize::Insert(selected),
+            ResizeKind::InsertAfter => StepResize::Insert(selected + 1),
+            ResizeKind::Remove => StepResize::Remove(selected),
+            ResizeKind::Resample => {
+                ui.add(Slider::new(&mut state.resample_size, MIN_SWATCH_SIZE..=MAX_SWATCH_SIZE).text("Steps"));
+                StepResize::Resample(state.resample_size)
+            }
+        };
+        if state.resize_kind != ResizeKind::Resample {
+            ui.label(format!("Relative to the selected step, {}", selected + 1));
+        }
+
+        // Edited colors are pinned first, so the preview shows them kept
+        let edited: Vec<(usize, Color32)> = (0..app.current_swatch().size)
+            .filter(|&step| state.hex_edit_state.was_edited(step))
+            .filter_map(|step| Some((step, state.hex_edit_state.get(step)?)))
+            .collect();
+        let before = &state.hex_edit_state.edited_colors;
+        let mut preview = app.current_swatch().clone();
+        for &(step, color) in &edited {
+            preview.pin_step(step, color);
+        }
+        let valid = resize.new_size(preview.size).is_some();
+        let dropped = preview.resize(resize);
+        let after = app.palette.swatch_display_colors(&preview);
+
+        egui::Grid::new("resize_preview").show(ui, |ui| {
+            ui.label(format!("Before ({})", before.len()));
+            draw_color_bar(ui, before, RESIZE_PREVIEW_WIDTH, RESIZE_PREVIEW_HEIGHT);
+            ui.end_row();
+            ui.label(format!("After ({})", after.len()));
+            draw_color_bar(ui, &after, RESIZE_PREVIEW_WIDTH, RESIZE_PREVIEW_HEIGHT);
+            ui.end_row();
+        });
+        if !dropped.is_empty() {
+            let steps: Vec<String> = dropped.iter().map(|step| (step + 1).to_string()).collect();
+            ui.colored_label(
+                Color32::from_rgb(230, 90, 80),
+                format!("Dropped, landing on another pinned step: {}", steps.join(", ")),
+            );
+        }
+
+        if ui.add_enabled(valid, egui::Button::new("Apply")).clicked() {
+            for (step, color) in edited {
+                state.hex_edit_state.clear_edit(step);
+                app.pin_color(step, color);
+            }
+            app.resize_current_swatch(resize);
+        }
+    });
+}
+
+/// Semantic names for individual steps (e.g. "surface" for step 2)
+fn draw_step_aliases_section(ui: &mut egui::Ui, app: &mut App) {
+    ui.collapsing("Step Aliases", |ui| {
+        let step_names = app.palette.step_naming.step_names(app.current_swatch().size);
+        let mut remove: Option<usize> = None;
+        let mut changed = false;
+
+        let swatch = app.current_swatch_mut();
+        for (i, alias) in swatch.step_aliases.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .add(egui::TextEdit::singleline(&mut alias.name).desired_width(100.0))
+                    .changed();
+                ui.label("=");
+                let selected = step_names.get(alias.step).cloned().unwrap_or_else(|| "(removed)".to_string());
+                egui::ComboBox::from_id_salt(("alias_step", i))
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        for (step, name) in step_names.iter().enumerate() {
+                            changed |= ui.selectable_value(&mut alias.step, step, name).changed();
+                        }
+                    });
+                if ui.button("×").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+
+        if let Some(i) = remove {
+            swatch.step_aliases.remove(i);
+            changed = true;
+        }
+        if ui.button("+ Add Alias").clicked() {
+            swatch.step_aliases.push(StepAlias {
+                step: 0,
+                name: String::new(),
+            });
+            changed = true;
+        }
+        if changed {
+            app.mark_dirty();
+        }
     });
 }
 
@@ -196,12 +384,12 @@ fn draw_control_points_section(ui: &mut egui::Ui, app: &mut App, state: &mut Swa
     ui.label("Control Points (drag to reorder):");
 
     // Get control point data - we use the index in the control_points vec
-    let control_points: Vec<(usize, u32, f32, Color32)> = app
+    let control_points: Vec<(usize, u32, f32, Color32, Option<ColorLink>)> = app
         .current_swatch()
         .control_points()
         .iter()
         .enumerate()
-        .map(|(idx, cp)| (idx, cp.id, cp.position, cp.color))
+        .map(|(idx, cp)| (idx, cp.id, cp.position, Color32::from(cp.color), cp.link.clone()))
         .collect();
 
     if control_points.is_empty() {
@@ -209,9 +397,10 @@ fn draw_control_points_section(ui: &mut egui::Ui, app: &mut App, state: &mut Swa
         return;
     }
 
+    let link_targets = link_targets(app);
     let mut actions: Vec<ControlPointAction> = Vec::new();
 
-    for (idx, id, pos, color) in &control_points {
+    for (idx, id, pos, color, link) in &control_points {
         let idx = *idx;
         let id = *id;
         
@@ -225,11 +414,44 @@ fn draw_control_points_section(ui: &mut egui::Ui, app: &mut App, state: &mut Swa
                 ui.label(format!("{:.0}%", pos * 100.0));
 
 
-                // Color picker
+                // Color picker (linked points take their color from the link)
                 let mut new_color = *color;
-                if ui.color_edit_button_srgba(&mut new_color).changed() {
-                    app.current_swatch_mut().set_control_point_color_by_id(id, new_color);
-                    app.regenerate_current_colors();
+                ui.add_enabled_ui(link.is_none(), |ui| {
+                    if color_edit_button_srgba(ui, &mut new_color, Alpha::OnlyBlend).changed() {
+                        app.current_swatch_mut().set_control_point_color_by_id(id, new_color);
+                        app.regenerate_current_colors();
+                    }
+                });
+
+                // Link display / link menu
+                match link {
+                    Some(link) => {
+                        let label = link_label(app, link);
+                        if app.palette.resolve_link(link).is_some() {
+                            ui.label(format!("→ {label}"));
+                        } else {
+                            ui.colored_label(Color32::from_rgb(230, 90, 80), format!("⚠ {label}"))
+                                .on_hover_text("Broken link: keeping the last known color");
+                        }
+                        if ui.small_button("Unlink").clicked() {
+                            actions.push(ControlPointAction::Link(id, None));
+                        }
+                    }
+                    None => {
+                        ui.menu_button("🔗", |ui| {
+                            if link_targets.is_empty() {
+                                ui.label("Add named colors or other swatches to link to");
+                            }
+                            for (label, target) in &link_targets {
+                                if ui.button(label).clicked() {
+                                    actions.push(ControlPointAction::Link(id, Some(target.clone())));
+                                    ui.close_menu();
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Link to a shared color");
+                    }
                 }
 
                 // Delete button
@@ -250,6 +472,10 @@ fn draw_control_points_section(ui: &mut egui::Ui, app: &mut App, state: &mut Swa
         }
     }
 
+    if let Some(error) = &state.link_error {
+        ui.colored_label(Color32::from_rgb(230, 90, 80), error);
+    }
+
     // Apply actions
     for action in actions {
         match action {
@@ -259,11 +485,56 @@ fn draw_control_points_section(ui: &mut egui::Ui, app: &mut App, state: &mut Swa
             ControlPointAction::Remove(id) => {
                 app.current_swatch_mut().remove_control_point_by_id(id);
             }
+            ControlPointAction::Link(id, link) => {
+                state.link_error = app.link_control_point(id, link).err().map(|e| e.to_string());
+            }
         }
         app.regenerate_current_colors();
     }
 }
 
+/// Everything a control point of the current swatch can link to: named colors
+/// and the control points of the other swatches
+fn link_targets(app: &App) -> Vec<(String, ColorLink)> {
+    let mut targets: Vec<(String, ColorLink)> = app
+        .palette
+        .named_colors
+        .iter()
+        .map(|named| (named.name.clone(), ColorLink::Named(named.name.clone())))
+        .collect();
+
+    for (index, swatch) in app.palette.swatches.iter().enumerate() {
+        if index == app.current_swatch_index {
+            continue;
+        }
+        for cp in swatch.control_points() {
+            let link = ColorLink::ControlPoint {
+                swatch_id: swatch.id(),
+                point_id: cp.id,
+            };
+            targets.push((link_label(app, &link), link));
+        }
+    }
+    targets
+}
+
+/// Human-readable description of a link target
+fn link_label(app: &App, link: &ColorLink) -> String {
+    match link {
+        ColorLink::Named(name) => name.clone(),
+        ColorLink::ControlPoint { swatch_id, point_id } => {
+            let Some(index) = app.palette.find_swatch_index_by_id(*swatch_id) else {
+                return "deleted swatch".to_string();
+            };
+            let swatch = &app.palette.swatches[index];
+            match swatch.control_points().iter().find(|cp| cp.id == *point_id) {
+                Some(cp) => format!("Swatch {} @ {:.0}%", index + 1, cp.position * 100.0),
+                None => format!("Swatch {} (deleted point)", index + 1),
+            }
+        }
+    }
+}
+
 fn draw_curve_editor(ui: &mut egui::Ui, app: &mut App, state: &mut SwatchEditorState) {
     egui::ComboBox::from_label("Curve Type")
         .selected_text(format!("{:?}", state.selected_curve_kind))
@@ -342,9 +613,48 @@ fn draw_curve_editor(ui: &mut egui::Ui, app: &mut App, state: &mut SwatchEditorS
     }
 }
 
+/// Alpha follows its own curve, so transparency can fade in or out at a
+/// different rate than the colors
+fn draw_alpha_curve_editor(ui: &mut egui::Ui, app: &mut App) {
+    let mut curve = app.current_swatch().alpha_curve;
+    let mut kind = curve.kind();
+    let mut changed = false;
+
+    egui::ComboBox::from_label("Alpha Curve")
+        .selected_text(format!("{:?}", kind))
+        .show_ui(ui, |ui| {
+            for option in [
+                CurveKind::Linear,
+                CurveKind::EaseIn,
+                CurveKind::EaseOut,
+                CurveKind::EaseInOut,
+            ] {
+                if ui.selectable_value(&mut kind, option, format!("{:?}", option)).changed() {
+                    curve = CurveType::from_kind(kind);
+                    changed = true;
+                }
+            }
+        });
+
+    if let CurveType::EaseIn(EaseIn { exponent })
+    | CurveType::EaseOut(EaseOut { exponent })
+    | CurveType::EaseInOut(EaseInOut { exponent }) = &mut curve
+    {
+        changed |= ui
+            .add(Slider::new(exponent, 0.5..=5.0).text("Alpha Exponent"))
+            .changed();
+    }
+
+    if changed {
+        app.current_swatch_mut().alpha_curve = curve;
+        app.regenerate_current_colors();
+    }
+}
+
 fn draw_color_values_section(ui: &mut egui::Ui, app: &mut App, state: &mut SwatchEditorState) {
     ui.collapsing("Colors (editable)", |ui| {
         ui.label("Edit colors. Changed colors show ● - Pin to create control point.");
+        ui.label("🔒 keeps a step's color while the rest of the ramp changes.");
         ui.add_space(4.0);
 
         let num_colors = state.hex_edit_state.edited_colors.len();
@@ -352,10 +662,16 @@ fn draw_color_values_section(ui: &mut egui::Ui, app: &mut App, state: &mut Swatc
 
         for i in 0..num_colors {
             let was_edited = state.hex_edit_state.was_edited(i);
+            let locked = app.current_swatch().is_step_locked(i);
             
             ui.horizontal(|ui| {
-                // Index
-                ui.label(format!("{:2}:", i + 1));
+                // Index (click to select)
+                if ui
+                    .selectable_label(i == app.selected_color_index, format!("{:2}:", i + 1))
+                    .clicked()
+                {
+                    app.select_color(i);
+                }
                 
                 // Edit indicator
                 if was_edited {
@@ -369,12 +685,24 @@ fn draw_color_values_section(ui: &mut egui::Ui, app: &mut App, state: &mut Swatc
                 draw_color_swatch(ui, color, Vec2::new(24.0, 16.0));
                 
                 let mut edit_color = color;
-                if ui.color_edit_button_srgba(&mut edit_color).changed() {
-                    action = Some(ColorAction::SetColor(i, edit_color));
-                }
+                ui.add_enabled_ui(!locked, |ui| {
+                    if color_edit_button_srgba(ui, &mut edit_color, Alpha::OnlyBlend)
+                        .on_disabled_hover_text("Unlock the step to edit it")
+                        .changed()
+                    {
+                        action = Some(ColorAction::SetColor(i, edit_color));
+                    }
+                });
                 
-                // Hex display
-                ui.label(format!("#{:02X}{:02X}{:02X}", color.r(), color.g(), color.b()));
+                // Hex display, with alpha for translucent colors
+                ui.label(hex(color).to_uppercase());
+
+                let lock_icon = if locked { "🔒" } else { "🔓" };
+                let lock_hint = "Lock this step at its color, edited or not. The lock anchors the curve, \
+                    so neighboring steps may shift and other unpinned edits are discarded.";
+                if ui.selectable_label(locked, lock_icon).on_hover_text(lock_hint).clicked() {
+                    action = Some(ColorAction::ToggleLock(i));
+                }
                 
                 // Pin/Revert buttons (only show when edited)
                 if was_edited {
@@ -395,31 +723,16 @@ fn draw_color_values_section(ui: &mut egui::Ui, app: &mut App, state: &mut Swatc
                     state.hex_edit_state.set(idx, color);
                 }
                 ColorAction::Pin(idx) => {
-                    // Calculate position based on index
-                    let position = if num_colors > 1 {
-                        idx as f32 / (num_colors - 1) as f32
-                    } else {
-                        0.5
-                    };
-                    
                     let color = state.hex_edit_state.get(idx).unwrap_or(Color32::BLACK);
-                    let tolerance = 0.5 / num_colors as f32;
-                    
-                    // Check if there's already a control point near this position
-                    if let Some(cp_idx) = app.current_swatch().has_control_point_at(position, tolerance) {
-                        // Update existing control point
-                        app.current_swatch_mut().set_control_point_color(cp_idx, color);
-                    } else {
-                        // Add new control point
-                        app.current_swatch_mut().add_control_point(position, color);
-                    }
-                    
                     state.hex_edit_state.clear_edit(idx);
-                    app.regenerate_current_colors();
+                    app.pin_color(idx, color);
                 }
                 ColorAction::Revert(idx) => {
                     state.hex_edit_state.clear_edit(idx);
                 }
+                ColorAction::ToggleLock(idx) => {
+                    state.toggle_step_lock(app, idx);
+                }
             }
         }
     });
@@ -0,0 +1,219 @@
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use egui_macroquad::egui::{self, Color32, Grid, ScrollArea, Slider, TextEdit, Vec2};
+
+use crate::app::App;
+use crate::clipboard::{hex_list, parse_color};
+use crate::color::{parse_hex_color, ColorSpace};
+use crate::curves::CurveType;
+use crate::fit::{solve_swatch, FitOptions, FitResult};
+use crate::palette::{MAX_SWATCH_SIZE, MIN_SWATCH_SIZE};
+use crate::ui::widgets::{draw_color_bar, draw_color_swatch};
+
+/// Size of the color squares in the residual table
+const SQUARE_SIZE: f32 = 16.0;
+/// Size of the ramp comparison bars
+const RAMP_PREVIEW_WIDTH: f32 = 240.0;
+const RAMP_PREVIEW_HEIGHT: f32 = 16.0;
+
+/// UI state for the swatch fitting window
+#[derive(Default)]
+pub struct SwatchFitState {
+    /// Target colors as typed or pasted, bright first
+    pub text: String,
+    pub options: FitOptions,
+    /// Targets and result of the last solve
+    targets: Vec<Color32>,
+    result: Option<FitResult>,
+    error: Option<String>,
+    /// The solve running on a worker thread, so the editor stays responsive
+    solving: Option<Receiver<FitResult>>,
+}
+
+impl SwatchFitState {
+    fn solve(&mut self) {
+        match parse_targets(&self.text) {
+            // The fitted swatch has a step per target, so the targets have
+            // to fit the sizes the editor allows
+            Ok(targets) if targets.len() < MIN_SWATCH_SIZE => {
+                self.error = Some(format!("Enter at least {MIN_SWATCH_SIZE} colors"));
+            }
+            Ok(targets) if targets.len() > MAX_SWATCH_SIZE => {
+                self.error = Some(format!("Enter at most {MAX_SWATCH_SIZE} colors, not {}", targets.len()));
+            }
+            Ok(targets) => {
+                let (sender, receiver) = mpsc::channel();
+                let (worker_targets, options) = (targets.clone(), self.options.clone());
+                thread::spawn(move || {
+                    let _ = sender.send(solve_swatch(&worker_targets, &options));
+                });
+                self.solving = Some(receiver);
+                self.targets = targets;
+                self.result = None;
+                self.error = None;
+            }
+            Err(token) => self.error = Some(format!("Not a color: {token}")),
+        }
+    }
+
+    /// Pick up the result once the worker is done
+    fn poll(&mut self, egui_ctx: &egui::Context) {
+        let Some(receiver) = &self.solving else { return };
+        match receiver.try_recv() {
+            Ok(result) => {
+                self.result = Some(result);
+                self.solving = None;
+            }
+            // Keep repainting so the result shows up without waiting for input
+            Err(TryRecvError::Empty) => egui_ctx.request_repaint(),
+            Err(TryRecvError::Disconnected) => {
+                self.error = Some("The solver stopped unexpectedly".to_string());
+                self.solving = None;
+            }
+        }
+    }
+}
+
+/// One color per line in any clipboard notation, or hex colors separated by
+/// spaces, commas or semicolons. Returns the first unreadable token on error.
+fn parse_targets(text: &str) -> Result<Vec<Color32>, String> {
+    let mut colors = Vec::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(color) = parse_color(line) {
+            colors.push(color);
+            continue;
+        }
+        for token in line
+            .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+            .filter(|token| !token.is_empty())
+        {
+            colors.push(parse_hex_color(token).ok_or_else(|| token.to_string())?);
+        }
+    }
+    Ok(colors)
+}
+
+/// Curve kind with its exponent, e.g. "EaseIn 1.85"
+fn curve_description(curve: &CurveType) -> String {
+    match curve {
+        CurveType::EaseIn(c) => format!("EaseIn {:.2}", c.exponent),
+        CurveType::EaseOut(c) => format!("EaseOut {:.2}", c.exponent),
+        CurveType::EaseInOut(c) => format!("EaseInOut {:.2}", c.exponent),
+        _ => format!("{:?}", curve.kind()),
+    }
+}
+
+/// Draw the "Fit Swatch to Colors" window: solve for an editable swatch that
+/// reproduces a hand-made ramp and show how far each step is off
+pub fn draw_swatch_fit(egui_ctx: &egui::Context, app: &mut App, state: &mut SwatchFitState, open: &mut bool) {
+    state.poll(egui_ctx);
+    egui::Window::new("Fit Swatch to Colors")
+        .open(open)
+        .default_width(320.0)
+        .show(egui_ctx, |ui| {
+            ui.label("Target colors, bright first:");
+            ui.add(
+                TextEdit::multiline(&mut state.text)
+                    .hint_text("#f2e8dc\n#c98a5e\n#3a2430")
+                    .desired_rows(6)
+                    .desired_width(f32::INFINITY)
+                    .code_editor(),
+            );
+            if ui.button("Use Current Swatch").clicked() {
+                state.text = hex_list(&app.generated_colors[app.current_swatch_index]);
+            }
+
+            ui.separator();
+            let options = &mut state.options;
+            egui::ComboBox::from_label("Color space")
+                .selected_text(options.color_space.map_or("Best fit", |space| space.name()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut options.color_space, None, "Best fit");
+                    for &space in ColorSpace::ALL {
+                        ui.selectable_value(&mut options.color_space, Some(space), space.name());
+                    }
+                });
+            ui.checkbox(&mut options.search_curves, "Search curve shapes");
+            ui.checkbox(&mut options.refine, "Refine control points")
+                .on_hover_text("Move control points off the target colors when that lowers the overall error");
+            ui.add(Slider::new(&mut options.max_control_points, 2..=16).text("Max control points"));
+            ui.add(
+                Slider::new(&mut options.tolerance, 0.001..=0.1)
+                    .logarithmic(true)
+                    .text("Tolerance (ΔE)"),
+            );
+
+            ui.horizontal(|ui| {
+                let solving = state.solving.is_some();
+                if ui.add_enabled(!solving, egui::Button::new("Solve")).clicked() {
+                    state.solve();
+                }
+                if solving {
+                    ui.spinner();
+                    ui.label("Solving...");
+                }
+            });
+            if let Some(error) = &state.error {
+                ui.colored_label(Color32::from_rgb(220, 90, 90), error);
+            }
+
+            let Some(result) = &mut state.result else { return };
+            // Report on the colors as the canvas shows them, which the output
+            // space may gamut map and which are always quantized
+            let fitted = app.palette.swatch_display_colors(&result.swatch);
+            result.measure_shown(&state.targets, &fitted);
+            ui.separator();
+            let swatch = &result.swatch;
+            ui.label(format!(
+                "{} control points, {} curve in {}",
+                swatch.control_points().len(),
+                curve_description(&swatch.interpolation_curve),
+                swatch.color_space.name(),
+            ));
+            ui.label(format!(
+                "ΔE max {:.4}, mean {:.4}",
+                result.max_residual(),
+                result.mean_residual()
+            ));
+
+            ui.horizontal(|ui| {
+                ui.label("Targets:");
+                draw_color_bar(ui, &state.targets, RAMP_PREVIEW_WIDTH, RAMP_PREVIEW_HEIGHT);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Fitted:  ");
+                draw_color_bar(ui, &fitted, RAMP_PREVIEW_WIDTH, RAMP_PREVIEW_HEIGHT);
+            });
+
+            let tolerance = state.options.tolerance;
+            ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                Grid::new("swatch_fit_residuals").striped(true).show(ui, |ui| {
+                    ui.strong("Step");
+                    ui.strong("Target");
+                    ui.strong("Fitted");
+                    ui.strong("ΔE");
+                    ui.end_row();
+                    let size = Vec2::splat(SQUARE_SIZE);
+                    for (step, ((&target, &color), &residual)) in
+                        state.targets.iter().zip(&fitted).zip(&result.residuals).enumerate()
+                    {
+                        ui.label((step + 1).to_string());
+                        draw_color_swatch(ui, target, size);
+                        draw_color_swatch(ui, color, size);
+                        let text = format!("{residual:.4}");
+                        if residual > tolerance {
+                            ui.colored_label(Color32::from_rgb(220, 90, 90), text);
+                        } else {
+                            ui.label(text);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+
+            if ui.button("Add as New Swatch").clicked() {
+                app.insert_swatch(app.current_swatch_index + 1, swatch.clone());
+            }
+        });
+}
@@ -1,35 +1,111 @@
-use egui_macroquad::egui::{self, TopBottomPanel};
+use egui_macroquad::egui::{self, Color32, DragValue, Slider, TopBottomPanel};
 
 use crate::app::App;
-use crate::viewport::Viewport;
+use crate::background::BackgroundKind;
+use crate::clipboard::ColorFormat;
+use crate::keymap::Action;
+use crate::rendering::LayoutMode;
+use crate::settings::Settings;
+use crate::ui::file_dialogs::FileRequest;
+
+/// Visibility of the tool windows opened from the menu bar
+#[derive(Default)]
+pub struct OpenWindows {
+    /// File menu command waiting to be handled by the file dialogs
+    pub file_request: Option<FileRequest>,
+    /// Menu command waiting to be run by the shortcut handler
+    pub action: Option<Action>,
+    pub image_extract: bool,
+    pub palette_preview: bool,
+    pub gradient_map: bool,
+    pub swatch_fit: bool,
+    pub export: bool,
+    pub lut_export: bool,
+    pub shortcuts: bool,
+    pub command_palette: bool,
+    pub background_compare: bool,
+    /// Message shown in the menu bar until dismissed, such as why a paste
+    /// did nothing
+    pub notice: Option<String>,
+}
 
 /// Draw the top menu panel
-pub fn draw_top_panel(egui_ctx: &egui::Context, app: &mut App) {
+pub fn draw_top_panel(
+    egui_ctx: &egui::Context,
+    app: &mut App,
+    windows: &mut OpenWindows,
+    settings: &mut Settings,
+    autosave_error: Option<&str>,
+) {
     TopBottomPanel::top("top_panel").show(egui_ctx, |ui| {
         ui.horizontal(|ui| {
-            ui.label("Chrma Palette Studio");
+            // macroquad can't retitle the window, so the title lives here
+            ui.label(format!("{} — Chrma Palette Studio", app.title()));
 
             ui.menu_button("File", |ui| {
                 if ui.button("New Palette").clicked() {
-                    // TODO: Implement new palette
+                    windows.file_request = Some(FileRequest::New);
                     ui.close_menu();
                 }
                 if ui.button("Load Palette...").clicked() {
-                    // TODO: Load logic here
+                    windows.file_request = Some(FileRequest::Open(None));
                     ui.close_menu();
                 }
-                if ui.button("Save Palette...").clicked() {
-                    // TODO: Save logic here
+                ui.add_enabled_ui(!settings.recent_files.is_empty(), |ui| {
+                    ui.menu_button("Recent Files", |ui| {
+                        for path in &settings.recent_files {
+                            let name = path.file_name().map_or_else(|| path.display().to_string(), |name| {
+                                name.to_string_lossy().into_owned()
+                            });
+                            if ui.button(name).on_hover_text(path.display().to_string()).clicked() {
+                                windows.file_request = Some(FileRequest::Open(Some(path.clone())));
+                                ui.close_menu();
+                            }
+                        }
+                        ui.separator();
+                        if ui.button("Clear Recent Files").clicked() {
+                            settings.recent_files.clear();
+                            settings.save();
+                            ui.close_menu();
+                        }
+                    });
+                });
+                if ui.button("Save Palette").clicked() {
+                    windows.file_request = Some(FileRequest::Save);
+                    ui.close_menu();
+                }
+                if ui.button("Save Palette As...").clicked() {
+                    windows.file_request = Some(FileRequest::SaveAs);
                     ui.close_menu();
                 }
                 ui.separator();
                 if ui.button("Export...").clicked() {
-                    // TODO: Export logic here
+                    windows.export = true;
                     ui.close_menu();
                 }
+                if ui.button("Export Lookup Textures...").clicked() {
+                    windows.lut_export = true;
+                    ui.close_menu();
+                }
+                ui.separator();
+                ui.menu_button("Autosave", |ui| {
+                    let mut changed = ui.checkbox(&mut settings.autosave_enabled, "Enabled").changed();
+                    ui.add_enabled_ui(settings.autosave_enabled, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Every");
+                            changed |= ui
+                                .add(DragValue::new(&mut settings.autosave_interval).range(5..=3600).suffix(" s"))
+                                .changed();
+                        });
+                    });
+                    if changed {
+                        settings.save();
+                    }
+                });
                 ui.separator();
                 if ui.button("Exit").clicked() {
-                    std::process::exit(0);
+                    windows.file_request = Some(FileRequest::Exit);
+                    ui.close_menu();
                 }
             });
 
@@ -42,25 +118,119 @@ pub fn draw_top_panel(egui_ctx: &egui::Context, app: &mut App) {
                     // TODO: Redo
                     ui.close_menu();
                 }
+                ui.separator();
+                ui.menu_button("Copy Color", |ui| {
+                    for (action, format) in [
+                        (Action::CopyColorHex, ColorFormat::Hex),
+                        (Action::CopyColorRgb, ColorFormat::Rgb),
+                        (Action::CopyColorOklch, ColorFormat::Oklch),
+                    ] {
+                        if ui.button(format.name()).clicked() {
+                            windows.action = Some(action);
+                            ui.close_menu();
+                        }
+                    }
+                });
+                for action in [Action::CopySwatchColors, Action::CopySwatch, Action::CopyPalette, Action::Paste] {
+                    if ui.button(action.name()).clicked() {
+                        windows.action = Some(action);
+                        ui.close_menu();
+                    }
+                }
             });
 
             ui.menu_button("View", |ui| {
                 if ui.button("Reset Viewport").clicked() {
-                    app.viewport = Viewport::default();
+                    app.viewport.reset();
                     ui.close_menu();
                 }
                 ui.separator();
                 if ui.button("Zoom In").clicked() {
-                    app.viewport.zoom *= 1.25;
+                    app.viewport.zoom_by(1.25);
                     ui.close_menu();
                 }
                 if ui.button("Zoom Out").clicked() {
-                    app.viewport.zoom *= 0.8;
+                    app.viewport.zoom_by(0.8);
                     ui.close_menu();
                 }
                 if ui.button("Zoom to Fit").clicked() {
-                    app.viewport.zoom = 1.0;
-                    app.viewport.offset = macroquad::prelude::Vec2::ZERO;
+                    app.zoom_to_fit();
+                    ui.close_menu();
+                }
+                if ui.button("Frame Selected Swatch").clicked() {
+                    app.frame_current_swatch();
+                    ui.close_menu();
+                }
+                ui.separator();
+                ui.menu_button("Layout", |ui| {
+                    let mut layout = app.layout;
+                    for &mode in LayoutMode::ALL {
+                        ui.radio_value(&mut layout.mode, mode, mode.name());
+                    }
+                    if layout.mode == LayoutMode::Centered {
+                        ui.separator();
+                        let steps = app.palette.swatches.iter().map(|s| s.size).max().unwrap_or(0);
+                        let step_label = |anchor: Option<usize>| match anchor {
+                            Some(step) => format!("Step {}", step + 1),
+                            None => "Middle".to_string(),
+                        };
+                        egui::ComboBox::from_label("Anchor")
+                            .selected_text(step_label(layout.anchor_step))
+                            .show_ui(ui, |ui| {
+                                for anchor in std::iter::once(None).chain((0..steps).map(Some)) {
+                                    ui.selectable_value(&mut layout.anchor_step, anchor, step_label(anchor));
+                                }
+                            });
+                    }
+                    app.set_layout(layout);
+                });
+                ui.menu_button("Background", |ui| {
+                    let background = &mut settings.background;
+                    let before = background.clone();
+                    for &kind in BackgroundKind::ALL {
+                        ui.radio_value(&mut background.kind, kind, kind.name());
+                    }
+                    match background.kind {
+                        BackgroundKind::Grey => {
+                            ui.separator();
+                            ui.add(Slider::new(&mut background.grey_lightness, 0.0..=1.0).text("Lightness"));
+                        }
+                        BackgroundKind::Custom => {
+                            ui.separator();
+                            ui.horizontal(|ui| {
+                                ui.label("Color:");
+                                ui.color_edit_button_srgba(&mut background.custom_color);
+                            });
+                        }
+                        BackgroundKind::Image => {
+                            ui.separator();
+                            ui.horizontal(|ui| {
+                                ui.label("File:");
+                                // The image is loaded from the path, so it's
+                                // only taken once editing ends
+                                let id = ui.id().with("background_image_path");
+                                let mut path = ui
+                                    .data_mut(|data| data.get_temp::<String>(id))
+                                    .unwrap_or_else(|| background.image_path.clone());
+                                let response = ui.text_edit_singleline(&mut path);
+                                if response.has_focus() {
+                                    ui.data_mut(|data| data.insert_temp(id, path));
+                                } else {
+                                    if response.lost_focus() {
+                                        background.image_path = path;
+                                    }
+                                    ui.data_mut(|data| data.remove::<String>(id));
+                                }
+                            });
+                        }
+                        _ => {}
+                    }
+                    if *background != before {
+                        settings.save_when_released();
+                    }
+                });
+                if ui.button("Compare on Backgrounds...").clicked() {
+                    windows.background_compare = true;
                     ui.close_menu();
                 }
             });
@@ -75,8 +245,49 @@ pub fn draw_top_panel(egui_ctx: &egui::Context, app: &mut App) {
                     app.duplicate_swatch(idx);
                     ui.close_menu();
                 }
+                ui.separator();
+                if ui.button("Fit Swatch to Colors...").clicked() {
+                    windows.swatch_fit = true;
+                    ui.close_menu();
+                }
+                if ui.button("Extract from Image...").clicked() {
+                    windows.image_extract = true;
+                    ui.close_menu();
+                }
+                if ui.button("Preview on Image...").clicked() {
+                    windows.palette_preview = true;
+                    ui.close_menu();
+                }
+                if ui.button("Gradient Map Sprite...").clicked() {
+                    windows.gradient_map = true;
+                    ui.close_menu();
+                }
+            });
+
+            ui.menu_button("Help", |ui| {
+                if ui.button("Command Palette...").clicked() {
+                    windows.command_palette = true;
+                    ui.close_menu();
+                }
+                if ui.button("Keyboard Shortcuts...").clicked() {
+                    windows.shortcuts = true;
+                    ui.close_menu();
+                }
             });
 
+            if let Some(error) = autosave_error {
+                ui.separator();
+                ui.colored_label(Color32::from_rgb(230, 90, 80), "Autosave failed")
+                    .on_hover_text(error);
+            }
+            if let Some(notice) = windows.notice.clone() {
+                ui.separator();
+                ui.colored_label(Color32::from_rgb(230, 90, 80), notice);
+                if ui.small_button("×").clicked() {
+                    windows.notice = None;
+                }
+            }
+
             // Display info
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 ui.label(format!(
@@ -86,6 +297,19 @@ pub fn draw_top_panel(egui_ctx: &egui::Context, app: &mut App) {
                     app.current_swatch().size,
                     app.viewport.zoom * 100.0
                 ));
+                ui.separator();
+                let unique = app.unique_colors().len();
+                match app.palette.color_budget {
+                    Some(budget) if unique > budget => {
+                        ui.colored_label(Color32::from_rgb(230, 90, 80), format!("Palette: {unique}/{budget}"));
+                    }
+                    Some(budget) => {
+                        ui.label(format!("Palette: {unique}/{budget}"));
+                    }
+                    None => {
+                        ui.label(format!("Palette: {unique}"));
+                    }
+                }
             });
         });
     });
@@ -1,6 +1,8 @@
 //! Custom egui widgets for the palette helper application
 
-use egui_macroquad::egui::{self, Color32, Rect, Response, Sense, Stroke, Ui, Vec2};
+use egui_macroquad::egui::{self, color_picker, Color32, Rect, Response, Sense, Stroke, TextureHandle, TextureOptions, Ui, Vec2};
+
+use crate::image_io::SourceImage;
 
 // =============================================================================
 // Drag and Drop List Support
@@ -251,7 +253,8 @@ pub fn draw_color_swatch(ui: &mut Ui, color: Color32, size: Vec2) -> Response {
     
     if ui.is_rect_visible(rect) {
         let painter = ui.painter();
-        painter.rect_filled(rect, 2.0, color);
+        // Translucent colors are shown over a checkerboard
+        color_picker::show_color_at(painter, color, rect);
         painter.rect_stroke(
             rect, 
             2.0, 
@@ -263,6 +266,23 @@ pub fn draw_color_swatch(ui: &mut Ui, color: Color32, size: Vec2) -> Response {
     response
 }
 
+// =============================================================================
+// Image Preview Widget
+// =============================================================================
+
+/// Upload an image to the GPU so it can be shown in egui
+pub fn load_image_texture(ctx: &egui::Context, name: &str, image: &SourceImage) -> TextureHandle {
+    let color_image = egui::ColorImage::from_rgba_unmultiplied([image.width, image.height], &image.to_rgba_bytes());
+    ctx.load_texture(name, color_image, TextureOptions::NEAREST)
+}
+
+/// Draw a texture scaled down to fit in a `max_size` square (never scaled up)
+pub fn draw_image_preview(ui: &mut Ui, texture: &TextureHandle, max_size: f32) -> Response {
+    let size = texture.size_vec2();
+    let scale = (max_size / size.x.max(size.y)).min(1.0);
+    ui.image((texture.id(), size * scale))
+}
+
 // =============================================================================
 // Utility Functions
 // =============================================================================
@@ -4,6 +4,11 @@ use macroquad::prelude::*;
 // Viewport: Handles camera panning and zooming
 // =============================================================================
 
+/// How quickly animated transitions settle (per second, exponential)
+const ANIMATION_RATE: f32 = 12.0;
+/// Screen pixels left around content framed by `frame`
+const FRAME_MARGIN: f32 = 24.0;
+
 pub struct Viewport {
     /// Camera offset in world coordinates (what world position is at screen center)
     pub offset: Vec2,
@@ -13,6 +18,11 @@ pub struct Viewport {
     min_zoom: f32,
     /// Maximum zoom level
     max_zoom: f32,
+    /// Screen area not covered by the UI, where framed content is placed
+    /// (None = the whole screen)
+    pub visible_area: Option<Rect>,
+    /// Offset and zoom an animated transition is heading to
+    target: Option<(Vec2, f32)>,
 }
 
 impl Default for Viewport {
@@ -22,6 +32,8 @@ impl Default for Viewport {
             zoom: 1.0,
             min_zoom: 0.25,
             max_zoom: 4.0,
+            visible_area: None,
+            target: None,
         }
     }
 }
@@ -62,9 +74,70 @@ impl Viewport {
             consumed = true;
         }
 
+        // Direct manipulation cancels any transition in progress
+        if consumed {
+            self.target = None;
+        }
         consumed
     }
 
+    /// Jump to an offset and zoom, cancelling any transition in progress
+    pub fn set_view(&mut self, offset: Vec2, zoom: f32) {
+        self.offset = offset;
+        self.zoom = zoom.clamp(self.min_zoom, self.max_zoom);
+        self.target = None;
+    }
+
+    /// Smoothly move to a new offset and zoom
+    pub fn animate_to(&mut self, offset: Vec2, zoom: f32) {
+        self.target = Some((offset, zoom.clamp(self.min_zoom, self.max_zoom)));
+    }
+
+    /// Animate a zoom about the screen center, relative to where any
+    /// transition in progress is heading (so repeated zooms add up)
+    pub fn zoom_by(&mut self, factor: f32) {
+        let (offset, zoom) = self.target.unwrap_or((self.offset, self.zoom));
+        self.animate_to(offset, zoom * factor);
+    }
+
+    /// Animate back to the default view
+    pub fn reset(&mut self) {
+        self.animate_to(Vec2::ZERO, 1.0);
+    }
+
+    /// Animate to show a world-space box as large as possible in the visible area
+    pub fn frame(&mut self, world: Rect) {
+        let area = self
+            .visible_area
+            .unwrap_or_else(|| Rect::new(0.0, 0.0, screen_width(), screen_height()));
+        let fit_width = (area.w - 2.0 * FRAME_MARGIN).max(1.0) / world.w.max(1.0);
+        let fit_height = (area.h - 2.0 * FRAME_MARGIN).max(1.0) / world.h.max(1.0);
+        let zoom = fit_width.min(fit_height).clamp(self.min_zoom, self.max_zoom);
+
+        // Put the box's center at the area's center
+        let screen_center = Vec2::new(screen_width() / 2.0, screen_height() / 2.0);
+        let offset = world.center() - (area.center() - screen_center) / zoom;
+        self.animate_to(offset, zoom);
+    }
+
+    /// Advance the transition in progress by `dt` seconds
+    pub fn update(&mut self, dt: f32) {
+        let Some((offset, zoom)) = self.target else {
+            return;
+        };
+        let t = 1.0 - (-ANIMATION_RATE * dt).exp();
+        // Zoom in log space so zooming in and out feel equally fast
+        self.zoom = (self.zoom.ln() + (zoom.ln() - self.zoom.ln()) * t).exp();
+        self.offset = self.offset.lerp(offset, t);
+
+        let settled = (self.zoom / zoom - 1.0).abs() < 0.001 && (self.offset - offset).length() * self.zoom < 0.5;
+        if settled {
+            self.offset = offset;
+            self.zoom = zoom;
+            self.target = None;
+        }
+    }
+
     /// Convert screen coordinates to world coordinates
     pub fn screen_to_world(&self, screen_pos: Vec2) -> Vec2 {
         let screen_center = Vec2::new(screen_width() / 2.0, screen_height() / 2.0);